@@ -0,0 +1,310 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Derive macros for the `icp` crate.
+//!
+//! `#[derive(ArgEnum)]` implements `icp::input::arg_parser::ArgEnum` for an
+//! enum with unit variants, so that the enum could be used as a command
+//! argument:
+//!
+//! ```ignore
+//! use icp::input::arg_parser::ArgEnum;
+//! use icp_derive::ArgEnum;
+//!
+//! #[derive(ArgEnum, PartialEq, Clone, Debug)]
+//! enum Duplex {
+//!     /// Both sides transmit at the same time.
+//!     Full,
+//!     /// Sides take turns transmitting.
+//!     #[arg(alias = "hd")]
+//!     Half,
+//!     #[arg(rename = "auto", hint = "auto (negotiate)")]
+//!     Negotiate,
+//! }
+//!
+//! let parser = Duplex::arg_parser();
+//! ```
+//!
+//! By default, the keyword for a variant is the variant name in kebab case:
+//! `HalfDuplex` becomes `half-duplex`.  Acronyms are kept together: `IOError`
+//! becomes `io-error`.  Variant attributes are:
+//!
+//! * `rename = "..."` - use a different keyword.
+//! * `alias = "..."` - also accept this keyword.  Could be repeated.
+//! * `hint = "..."` - show this in the hint, instead of the keyword.
+//!
+//! `#[arg(hint = "...")]` on the enum itself replaces the whole hint.
+//!
+//! Doc comments on variants become the variant descriptions.
+
+extern crate proc_macro;
+
+use std::collections::HashSet;
+use std::iter::once;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Lit, Meta,
+    NestedMeta, Result,
+};
+
+#[proc_macro_derive(ArgEnum, attributes(arg))]
+pub fn derive_arg_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    arg_enum(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Settings from an `#[arg(...)]` attribute.
+#[derive(Default)]
+struct ArgAttrs {
+    rename: Option<String>,
+    aliases: Vec<String>,
+    hint: Option<String>,
+}
+
+fn arg_attrs(attrs: &[Attribute]) -> Result<ArgAttrs> {
+    let mut res = ArgAttrs::default();
+
+    for attr in attrs.iter().filter(|a| a.path.is_ident("arg")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    "expected `#[arg(name = \"value\", ...)]`",
+                ))
+            }
+        };
+
+        for nested in list.nested {
+            let (path, value) = match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) => match nv.lit {
+                    Lit::Str(value) => (nv.path, value.value()),
+                    lit => {
+                        return Err(Error::new(
+                            lit.span(),
+                            "expected a string literal",
+                        ))
+                    }
+                },
+                nested => {
+                    return Err(Error::new(
+                        nested.span(),
+                        "expected `name = \"value\"`",
+                    ))
+                }
+            };
+
+            if path.is_ident("rename") {
+                res.rename = Some(value);
+            } else if path.is_ident("alias") {
+                res.aliases.push(value);
+            } else if path.is_ident("hint") {
+                res.hint = Some(value);
+            } else {
+                return Err(Error::new(
+                    path.span(),
+                    "unknown `arg` attribute, expected `rename`, `alias` or \
+                     `hint`",
+                ));
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// Joins all the lines of the doc comments into a single line.
+fn description(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(line) => Some(line.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
+
+/// `HalfDuplex` becomes `half-duplex`.  Runs of upper case letters are treated
+/// as a single word, so `HTTPServer` becomes `http-server`.
+fn kebab_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut res = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // An upper case letter that follows a lower case one starts a new
+            // word.  Otherwise, it is a part of an acronym, like `IO` or
+            // `I2C`, and only the last letter of an acronym could start a new
+            // word, when it is followed by a lower case letter.
+            let prev_lowercase = i != 0 && chars[i - 1].is_lowercase();
+            let next_lowercase =
+                matches!(chars.get(i + 1), Some(next) if next.is_lowercase());
+            if i != 0 && (prev_lowercase || next_lowercase) {
+                res.push('-');
+            }
+            res.extend(c.to_lowercase());
+        } else if c == '_' {
+            res.push('-');
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+fn quote_option(value: Option<String>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { ::std::option::Option::Some(#value) },
+        None => quote! { ::std::option::Option::None },
+    }
+}
+
+fn arg_enum(input: DeriveInput) -> Result<TokenStream2> {
+    let data = match input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`ArgEnum` could only be derived for enums",
+            ))
+        }
+    };
+
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            input.ident.span(),
+            "`ArgEnum` requires at least one variant",
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let mut variants = vec![];
+    // Keywords and aliases of all the variants, as every spelling should select
+    // only one variant.
+    let mut spellings = HashSet::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new(
+                variant.span(),
+                "`ArgEnum` only supports variants without fields",
+            ));
+        }
+
+        let attrs = arg_attrs(&variant.attrs)?;
+        let ident = &variant.ident;
+        let keyword = attrs
+            .rename
+            .unwrap_or_else(|| kebab_case(&ident.to_string()));
+        let aliases = &attrs.aliases;
+        for spelling in once(&keyword).chain(aliases) {
+            if !spellings.insert(spelling.clone()) {
+                return Err(Error::new(
+                    variant.span(),
+                    format!("keyword `{}` is used more than once", spelling),
+                ));
+            }
+        }
+        let hint = quote_option(attrs.hint);
+        let description = quote_option(description(&variant.attrs));
+
+        variants.push(quote! {
+            ::icp::input::arg_parser::ArgEnumVariant {
+                value: #name::#ident,
+                keyword: #keyword,
+                aliases: &[ #( #aliases ),* ],
+                hint: #hint,
+                description: #description,
+            }
+        });
+    }
+
+    let enum_hint = match arg_attrs(&input.attrs)? {
+        ArgAttrs {
+            rename: None,
+            aliases,
+            hint,
+        } if aliases.is_empty() => quote_option(hint),
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "only `hint` is supported in `arg` attributes on the enum",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::icp::input::arg_parser::ArgEnum
+            for #name #ty_generics #where_clause
+        {
+            fn variants()
+                -> ::std::vec::Vec<::icp::input::arg_parser::ArgEnumVariant<Self>>
+            {
+                ::std::vec![ #( #variants ),* ]
+            }
+
+            fn hint() -> ::std::option::Option<&'static str> {
+                #enum_hint
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::arg_enum;
+
+    fn derive_error(input: &str) -> String {
+        match arg_enum(syn::parse_str(input).unwrap()) {
+            Ok(_) => panic!("Derive succeeded for: {}", input),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn repeated_keywords() {
+        assert_eq!(
+            derive_error("enum E { Full, #[arg(rename = \"full\")] Half }"),
+            "keyword `full` is used more than once"
+        );
+        assert_eq!(
+            derive_error("enum E { #[arg(alias = \"half\")] Full, Half }"),
+            "keyword `half` is used more than once"
+        );
+        assert_eq!(
+            derive_error(
+                "enum E { #[arg(alias = \"f\", alias = \"f\")] Full }"
+            ),
+            "keyword `f` is used more than once"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use icp::input::arg_parser::test_utils::build_cf_parse_checkers;
+use icp::input::arg_parser::{
+    ArgEnum, ContextFreeArgParser, SuggestionDetails,
+};
+use icp_derive::ArgEnum;
+
+#[derive(ArgEnum, PartialEq, Clone, Debug)]
+enum Duplex {
+    /// Both sides transmit at the same time.
+    Full,
+    /// Sides take turns
+    /// transmitting.
+    #[arg(alias = "hd", alias = "half-duplex")]
+    Half,
+    #[arg(rename = "auto", hint = "auto (negotiate)")]
+    Negotiate,
+    LinkDown,
+}
+
+#[derive(ArgEnum, PartialEq, Clone, Debug)]
+enum Acronym {
+    HTTPServer,
+    IOError,
+    UseTLS,
+    I2CBus,
+    Baud115200Rate,
+}
+
+#[derive(ArgEnum, PartialEq, Clone, Debug)]
+#[arg(hint = "<level>")]
+enum Level {
+    Low,
+    High,
+}
+
+#[test]
+fn variants() {
+    let parser = Duplex::arg_parser();
+    let expected_hint = &["full", "half", "auto (negotiate)", "link-down"];
+
+    let described = |keyword: &str, description: &str| {
+        let details = SuggestionDetails {
+            description: Some(description.to_string()),
+            ..SuggestionDetails::default()
+        };
+        (keyword.to_string(), details)
+    };
+    assert_eq!(
+        parser.suggestion_details(""),
+        vec![
+            described("full", "Both sides transmit at the same time."),
+            described("half", "Sides take turns transmitting."),
+        ]
+    );
+
+    let (check_hint, check_suggestions, check_parse, check_failure) =
+        build_cf_parse_checkers("parser", parser);
+
+    check_hint(expected_hint);
+
+    check_parse("full", Duplex::Full);
+    check_parse("half", Duplex::Half);
+    check_parse("hd", Duplex::Half);
+    check_parse("half-duplex", Duplex::Half);
+    check_parse("auto", Duplex::Negotiate);
+    check_parse("link-down", Duplex::LinkDown);
+
+    check_failure("negotiate", 0, expected_hint);
+    check_failure("Full", 0, expected_hint);
+    check_failure("link", 4, expected_hint);
+    check_failure(
+        "h",
+        1,
+        &[
+            "full",
+            "half",
+            "auto (negotiate)",
+            "link-down",
+            "hd (alias of half)",
+            "half-duplex (alias of half)",
+        ],
+    );
+
+    check_suggestions("", &["full", "half", "auto", "link-down"]);
+    check_suggestions("l", &["link-down"]);
+}
+
+#[test]
+fn enum_hint() {
+    let (check_hint, check_suggestions, check_parse, check_failure) =
+        build_cf_parse_checkers("parser", Level::arg_parser());
+
+    check_hint(&["<level>"]);
+    check_parse("low", Level::Low);
+    check_parse("high", Level::High);
+    check_failure("medium", 0, &["<level>"]);
+    check_suggestions("", &["low", "high"]);
+}
+
+#[test]
+fn acronyms() {
+    let expected_hint = &[
+        "http-server",
+        "io-error",
+        "use-tls",
+        "i2c-bus",
+        "baud115200-rate",
+    ];
+
+    let (check_hint, _check_suggestions, check_parse, _check_failure) =
+        build_cf_parse_checkers("parser", Acronym::arg_parser());
+
+    check_hint(expected_hint);
+    check_parse("http-server", Acronym::HTTPServer);
+    check_parse("io-error", Acronym::IOError);
+    check_parse("use-tls", Acronym::UseTLS);
+    check_parse("i2c-bus", Acronym::I2CBus);
+    check_parse("baud115200-rate", Acronym::Baud115200Rate);
+}
@@ -43,6 +43,7 @@ pub use table::CommandsTable;
 
 use std::fmt;
 
+use crate::input::arg_parser::SuggestionDetails;
 use crate::input::command_parser::{CommandParseRes, CommandSuggestions};
 
 /// An end of line hint may refer either to the whole input or to a specific
@@ -75,6 +76,7 @@ pub struct ParseRes {
     pub completion: Option<String>,
     pub end_of_line_hint: Option<EndOfLineHint>,
     pub suggestions: Vec<String>,
+    pub suggestion_details: Vec<(String, SuggestionDetails)>,
     pub usage: Option<String>,
     pub command: Option<Box<dyn Executor>>,
 }
@@ -86,6 +88,7 @@ impl fmt::Debug for ParseRes {
             .field("completion", &self.completion)
             .field("end_of_line_hint", &self.end_of_line_hint)
             .field("suggestions", &self.suggestions)
+            .field("suggestion_details", &self.suggestion_details)
             .field("usage", &self.usage)
             .field(
                 "command",
@@ -126,6 +129,21 @@ pub trait Command {
         CommandParseRes<Box<dyn Executor>>,
         Option<CommandSuggestions>,
     );
+
+    /// Details for some of the suggestions [`parse()`] produces for the same
+    /// `input` and `pos`.  See [`CommandParser::suggestion_details()`].
+    /// Default implementation returns no details.
+    ///
+    /// [`parse()`]: Self::parse
+    /// [`CommandParser::suggestion_details()`]:
+    ///     crate::input::command_parser::CommandParser::suggestion_details
+    fn suggestion_details(
+        &self,
+        _input: &str,
+        _pos: usize,
+    ) -> Vec<(String, SuggestionDetails)> {
+        vec![]
+    }
 }
 
 /// When command is parsed its arguments are stored in a parsed form inside an
@@ -20,7 +20,9 @@ use std::cell::RefCell;
 use std::cmp::max;
 use std::rc::Rc;
 
-use crate::input::arg_parser::keyword_set_with_hint;
+use crate::input::arg_parser::{
+    keyword_set_with_descriptions_and_hint, SuggestionDetails,
+};
 use crate::input::command_parser::alternatives::AlternativesCommandParser;
 use crate::input::command_parser::{
     alternatives_cmd, command_1arg, command_no_args, CommandParser,
@@ -110,8 +112,8 @@ impl Help {
                 let inner = inner.clone();
                 let terminal = terminal.clone();
 
-                let arg1 = keyword_set_with_hint(
-                    table.iter().map(|c| c.keyword()),
+                let arg1 = keyword_set_with_descriptions_and_hint(
+                    table.iter().map(|c| (c.keyword(), c.short_usage())),
                     &["<command name>"],
                 );
 
@@ -175,6 +177,21 @@ impl Command for Help {
             Inner::Initialized { parser, .. } => parser.parse(input, pos),
         }
     }
+
+    fn suggestion_details(
+        &self,
+        input: &str,
+        pos: usize,
+    ) -> Vec<(String, SuggestionDetails)> {
+        match &*self.inner.borrow() {
+            Inner::Uninitialized => panic!(
+                "`suggestion_details` called before `set_commands()` was called"
+            ),
+            Inner::Initialized { parser, .. } => {
+                parser.suggestion_details(input, pos)
+            }
+        }
+    }
 }
 
 impl Inner {
@@ -220,16 +237,30 @@ impl Inner {
 }
 
 pub fn all_commands_usage(table: CommandsTable) -> Vec<String> {
-    let max_width = table.iter().map(|c| c.keyword().len()).fold(0, max);
+    keywords_usage(table.iter().map(|c| (c.keyword(), c.short_usage())))
+}
+
+/// Formats keywords and their descriptions as a two column list, with the
+/// descriptions aligned.  Suitable for inclusion into a help message.  See
+/// [`KeywordSetArgParser::descriptions()`].
+///
+/// [`KeywordSetArgParser::descriptions()`]:
+///     crate::input::arg_parser::keyword_set::KeywordSetArgParser::descriptions
+pub fn keywords_usage<'a, Keywords>(keywords: Keywords) -> Vec<String>
+where
+    Keywords: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let keywords = keywords.into_iter().collect::<Vec<_>>();
+    let max_width = keywords.iter().map(|(k, _)| k.len()).fold(0, max);
 
-    table
-        .iter()
-        .map(|command| {
+    keywords
+        .into_iter()
+        .map(|(keyword, description)| {
             format!(
-                "  {keyword:max_width$}    {short_usage}",
-                keyword = command.keyword(),
+                "  {keyword:max_width$}    {description}",
+                keyword = keyword,
                 max_width = max_width,
-                short_usage = command.short_usage(),
+                description = description,
             )
         })
         .collect()
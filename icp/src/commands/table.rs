@@ -22,6 +22,7 @@ use std::iter::once;
 use std::ops::Deref;
 use std::rc::{Rc, Weak};
 
+use crate::input::arg_parser::SuggestionDetails;
 use crate::input::command_parser::{CommandParseFailure, CommandParseRes};
 use crate::input::common_prefix;
 use crate::TerminalContentRef;
@@ -141,6 +142,7 @@ fn empty_input(commands: &[Box<dyn Command>]) -> ParseRes {
             .iter()
             .map(|k| k.keyword().to_string())
             .collect::<Vec<_>>(),
+        suggestion_details: short_usages(commands.iter().map(|c| c.as_ref())),
         usage: Some("Waiting for a command".to_string()),
         command: None,
     }
@@ -156,6 +158,7 @@ fn no_match() -> ParseRes {
             text: "TODO no_match".to_string(),
         }),
         suggestions: vec![],
+        suggestion_details: vec![],
         usage: Some("TODO: usage".to_string()),
         command: None,
     }
@@ -171,11 +174,30 @@ fn prefix_command_no_hints() -> ParseRes {
             text: "TODO prefix_command_no_hints".to_string(),
         }),
         suggestions: vec![],
+        suggestion_details: vec![],
         usage: Some("TODO: prefix_command_no_hints usage".to_string()),
         command: None,
     }
 }
 
+/// Command short usage is used as a description of the command keyword in the
+/// suggestions list.
+fn short_usages<'a>(
+    commands: impl Iterator<Item = &'a dyn Command>,
+) -> Vec<(String, SuggestionDetails)> {
+    commands
+        .map(|c| {
+            (
+                c.keyword().to_string(),
+                SuggestionDetails {
+                    description: Some(c.short_usage().to_string()),
+                    ..SuggestionDetails::default()
+                },
+            )
+        })
+        .collect()
+}
+
 fn hint_and_completion<'a>(
     prefix: &str,
     suggestions: impl Iterator<Item = &'a str>,
@@ -214,6 +236,7 @@ fn prefix_command(prefix: &str, commands: &[&dyn Command]) -> ParseRes {
             text: "<command>".to_string(),
         }),
         suggestions,
+        suggestion_details: short_usages(commands.iter().copied()),
         usage: Some("TODO: prefix_command usage".to_string()),
         command: None,
     }
@@ -229,24 +252,29 @@ fn parse_args(
         ArgumentParseFailed, ExpectedArg, UnexpectedArgument,
     };
 
-    match command.parse(args, pos) {
-        (CommandParseRes::Parsed(exec), suggestions) => ParseRes {
+    let (res, suggestions) = command.parse(args, pos);
+    let suggestions = suggestions
+        .map(Into::<Vec<String>>::into)
+        .unwrap_or_default();
+    let suggestion_details = match pos {
+        Some(pos) => command.suggestion_details(args, pos),
+        None => vec![],
+    };
+
+    match res {
+        CommandParseRes::Parsed(exec) => ParseRes {
             inline_hint: None,
             completion: None,
             end_of_line_hint: None,
-            suggestions: suggestions
-                .map(Into::<Vec<String>>::into)
-                .unwrap_or_default(),
+            suggestions,
+            suggestion_details,
             usage: Some("TODO: parse_args usage".to_string()),
             command: Some(exec),
         },
-        (
-            CommandParseRes::Failed {
-                parsed_up_to: _,
-                reason: ArgumentParseFailed { from, to, reason },
-            },
-            suggestions,
-        ) => ParseRes {
+        CommandParseRes::Failed {
+            parsed_up_to: _,
+            reason: ArgumentParseFailed { from, to, reason },
+        } => ParseRes {
             inline_hint: None,
             completion: None,
             end_of_line_hint: Some(EndOfLineHint {
@@ -254,17 +282,15 @@ fn parse_args(
                 type_: HintType::Error,
                 text: reason.join(" | "),
             }),
-            suggestions: suggestions.map(Into::into).unwrap_or_default(),
+            suggestions,
+            suggestion_details,
             usage: Some("TODO: parse_args usage".to_string()),
             command: None,
         },
-        (
-            CommandParseRes::Failed {
-                parsed_up_to: _,
-                reason: ExpectedArg { index: _, hint },
-            },
-            suggestions,
-        ) => ParseRes {
+        CommandParseRes::Failed {
+            parsed_up_to: _,
+            reason: ExpectedArg { index: _, hint },
+        } => ParseRes {
             inline_hint: None,
             completion: None,
             end_of_line_hint: Some(EndOfLineHint {
@@ -272,17 +298,15 @@ fn parse_args(
                 type_: HintType::Error,
                 text: hint.join(" | "),
             }),
-            suggestions: suggestions.map(Into::into).unwrap_or_default(),
+            suggestions,
+            suggestion_details,
             usage: Some("TODO: parse_args usage".to_string()),
             command: None,
         },
-        (
-            CommandParseRes::Failed {
-                parsed_up_to: _,
-                reason: UnexpectedArgument { from },
-            },
-            suggestions,
-        ) => ParseRes {
+        CommandParseRes::Failed {
+            parsed_up_to: _,
+            reason: UnexpectedArgument { from },
+        } => ParseRes {
             inline_hint: None,
             completion: None,
             end_of_line_hint: Some(EndOfLineHint {
@@ -290,7 +314,8 @@ fn parse_args(
                 type_: HintType::Error,
                 text: "Unexpected argument".to_string(),
             }),
-            suggestions: suggestions.map(Into::into).unwrap_or_default(),
+            suggestions,
+            suggestion_details,
             usage: Some("TODO: parse_args usage".to_string()),
             command: None,
         },
@@ -18,6 +18,7 @@
 pub mod arg_parser;
 pub mod command_parser;
 pub mod common_prefix;
+pub mod fuzzy_match;
 
 mod history;
 
@@ -29,6 +30,7 @@ use crate::str_byte_pos;
 
 pub use common_prefix::common_prefix;
 
+use arg_parser::SuggestionDetails;
 use history::History;
 
 /// Prompt text may be different depending on whether the entered text forms a
@@ -129,6 +131,11 @@ pub struct Input {
     /// complete the text of the command argument that contains the cursor.
     suggestions: Vec<String>,
 
+    /// Additional information for some of the `suggestions`, each paired with
+    /// the suggestion it describes.  Frontends may show it next to the
+    /// suggestion, but are free to ignore it.
+    suggestion_details: Vec<(String, SuggestionDetails)>,
+
     /// A free form text regarding the current command usage.  If specified it
     /// is shown below the `suggestions` (if any).  It is recommended that this
     /// text contains an "outline" of the command, if a full command has been
@@ -161,6 +168,7 @@ impl Input {
             completion: None,
             end_of_line_hint: None,
             suggestions: vec![],
+            suggestion_details: vec![],
             usage,
             command: None,
             history: History::new(),
@@ -205,6 +213,10 @@ impl Input {
         self.suggestions.as_slice()
     }
 
+    pub fn suggestion_details(&self) -> &[(String, SuggestionDetails)] {
+        self.suggestion_details.as_slice()
+    }
+
     pub fn usage(&self) -> Option<&str> {
         self.usage.as_deref()
     }
@@ -354,6 +366,7 @@ impl Input {
             completion,
             end_of_line_hint,
             suggestions,
+            suggestion_details,
             usage,
             command,
         } = self.commands.parse(&self.input, self.pos);
@@ -362,6 +375,7 @@ impl Input {
         self.completion = completion;
         self.end_of_line_hint = end_of_line_hint;
         self.suggestions = suggestions;
+        self.suggestion_details = suggestion_details;
         self.usage = usage;
         self.command = command;
     }
@@ -27,29 +27,106 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::rc::Rc;
 
-use map::{Arg2Map, ContextFreeMap};
+use map::{
+    Arg2Map, Arg2TryMap, Arg2Validate, ContextFreeMap, ContextFreeTryMap,
+    ContextFreeValidate,
+};
 
 pub mod alternatives;
+pub mod arg_enum;
+pub mod boolean;
+pub mod date_time;
+pub mod duration;
+pub mod dynamic_keyword_set;
+pub mod expr;
 pub mod file;
+pub mod flags;
+pub mod float;
+pub mod hex_bytes;
+pub mod int_range;
+pub mod key_value;
 pub mod keyword_set;
+pub mod list;
+pub mod mac_addr;
 pub mod map;
+pub mod net;
+pub mod optional;
 pub mod prim_int;
+pub mod quantity;
+pub mod radix_int;
+pub mod regex_string;
+pub mod repeated;
+pub mod symbolic_int;
+pub mod uuid;
 
 pub mod test_utils;
 
 #[cfg(test)]
 pub use alternatives::alternatives_arg2;
 pub use alternatives::alternatives_cf;
+pub use arg_enum::{arg_enum, ArgEnum, ArgEnumVariant};
+pub use boolean::{boolean, boolean_with_synonyms};
+pub use date_time::{date_time, date_time_with_name, Clock, SystemClock};
+pub use duration::{
+    duration, duration_for_range, duration_for_range_and_name,
+    duration_with_name,
+};
+pub use dynamic_keyword_set::{
+    dynamic_keyword_set, dynamic_keyword_set_with_hint, KeywordProvider,
+};
+pub use expr::{
+    expr, expr_for_range, expr_for_range_and_name, expr_with_name, NoSymbols,
+    SymbolProvider,
+};
 #[cfg(test)]
 pub use file::file;
 pub use file::file_for_current_dir;
-pub use keyword_set::{keyword_set, keyword_set_with_hint};
+pub use flags::flags;
+pub use float::{
+    float, float_for_range, float_for_range_and_name, float_with_name,
+};
+pub use hex_bytes::{
+    hex_bytes, hex_bytes_for_len, hex_bytes_for_len_and_name,
+    hex_bytes_with_name,
+};
+pub use int_range::{
+    int_range, int_range_with_name, int_range_within, int_range_within_and_name,
+};
+pub use key_value::key_value;
+pub use keyword_set::{
+    keyword_set, keyword_set_with_descriptions,
+    keyword_set_with_descriptions_and_hint, keyword_set_with_hint,
+};
+pub use list::list_of;
+pub use mac_addr::{
+    eui64, eui64_with_name, mac_addr, mac_addr_with_name, Eui64, MacAddr,
+};
+pub use net::{
+    host_port, host_port_with_name, ip_addr, ip_addr_with_name, ip_net,
+    ip_net_with_name, socket_addr, socket_addr_with_name, HostPort, IpNet,
+};
+pub use optional::{optional, with_default};
 #[cfg(test)]
 pub use prim_int::{prim_int, prim_int_for_range};
 pub use prim_int::{prim_int_for_range_and_name, prim_int_with_name};
+pub use quantity::{
+    binary_units, quantity, quantity_for_range, quantity_for_range_and_name,
+    quantity_with_name, si_units, Unit,
+};
+pub use radix_int::{
+    radix_int, radix_int_for_range, radix_int_for_range_and_name,
+    radix_int_with_name, Radix,
+};
+pub use regex_string::{string_matching, string_matching_with_name};
+pub use repeated::{at_least, between, many};
+pub use symbolic_int::{
+    symbolic_int, symbolic_int_for_range, symbolic_int_for_range_and_name,
+    symbolic_int_with_name,
+};
+pub use uuid::{uuid, uuid_with_name, Uuid};
 
 /// Result of parsing an argument.  Value returned by the
 /// [`ContextFreeArgParser::parse()`] and [`Arg2Parser::parse()`] methods.
@@ -78,6 +155,44 @@ pub enum ArgParseRes<Res> {
     Parsed(Res),
 }
 
+/// Additional information about a suggestion, that frontends may show next to
+/// it.  See [`ContextFreeArgParser::suggestion_details()`].
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct SuggestionDetails {
+    /// One line description of the suggested value.
+    pub description: Option<String>,
+    /// Character positions in the suggestion, that matched the prefix, so
+    /// that frontends could highlight them.  Empty when the suggestion simply
+    /// starts with the prefix.
+    pub matched: Vec<usize>,
+}
+
+/// Prepends `prefix` to every suggestion in `details`, moving the matched
+/// positions along.  For parsers that suggest values for a part of the
+/// argument, as suggestions always complete the whole argument.
+pub(crate) fn prefix_suggestion_details(
+    prefix: &str,
+    details: Vec<(String, SuggestionDetails)>,
+) -> Vec<(String, SuggestionDetails)> {
+    let shift = prefix.chars().count();
+    details
+        .into_iter()
+        .map(|(suggestion, details)| {
+            (
+                format!("{}{}", prefix, suggestion),
+                SuggestionDetails {
+                    matched: details
+                        .matched
+                        .into_iter()
+                        .map(|i| i + shift)
+                        .collect(),
+                    ..details
+                },
+            )
+        })
+        .collect()
+}
+
 impl<Res> ArgParseRes<Res> {
     /// Combines two `ArgParseRes` results, giving `self` preference in case
     /// they are considered equal.
@@ -138,6 +253,44 @@ pub trait ContextFreeArgParser<Res> {
     /// elements of the vector.
     fn hint(&self) -> Vec<String>;
 
+    /// Details for some of the values [`suggestion`] returns for the same
+    /// `prefix`, each paired with the suggestion it describes.  Most parsers
+    /// have nothing to add.
+    ///
+    /// [`suggestion`]: Self::suggestion
+    fn suggestion_details(
+        &self,
+        _prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        vec![]
+    }
+
+    /// Part of `input` that caused [`parse`] to fail, when a parser can point
+    /// at it more precisely than the whole argument.  Like a single invalid
+    /// token in an expression.  Only meaningful when [`parse`] fails for the
+    /// same `input`.  `None` means that the whole argument is at fault, and
+    /// this is what most parsers return.
+    ///
+    /// [`parse`]: Self::parse
+    fn failure_span(&self, _input: &str) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Value to use when the argument is not present in the input at all.
+    /// `None` means that the argument is required, and this is what most
+    /// parsers return.  See [`optional::optional()`] and
+    /// [`optional::with_default()`].
+    fn absent_value(&self) -> Option<Res> {
+        None
+    }
+
+    /// When `true`, the command parser gives this parser all of the remaining
+    /// input, starting from the current word, instead of a single word.  See
+    /// [`repeated::many()`].
+    fn consumes_rest(&self) -> bool {
+        false
+    }
+
     /// Creates a new parser that maps the result of the current parser using a
     /// function.
     fn map<F, B>(self, f: F) -> ContextFreeMap<Res, B, Self, F>
@@ -148,6 +301,33 @@ pub trait ContextFreeArgParser<Res> {
         ContextFreeMap::new(self, f)
     }
 
+    /// Creates a new parser that maps the result of the current parser using a
+    /// function that may reject the value.  An `Err` is reported as a parse
+    /// failure, with the error as the reason.  An absent value that `f`
+    /// rejects makes the argument required.
+    fn try_map<F, B>(self, f: F) -> ContextFreeTryMap<Res, B, Self, F>
+    where
+        F: Fn(Res) -> Result<B, String>,
+        Self: Sized,
+    {
+        ContextFreeTryMap::new(self, f)
+    }
+
+    /// Creates a new parser that rejects values that do not satisfy `pred`,
+    /// using `message` as the failure reason.  Absent values are not checked.
+    fn validate<P, Message>(
+        self,
+        pred: P,
+        message: Message,
+    ) -> ContextFreeValidate<Res, Self, P>
+    where
+        P: Fn(&Res) -> bool,
+        Message: ToString,
+        Self: Sized,
+    {
+        ContextFreeValidate::new(self, pred, message.to_string())
+    }
+
     /// Allows a context free parser to be used as a non-context free parser, as
     /// `ContextFreeAdapter` implements `Arg2Parser` and friends.
     fn adapt(self) -> ContextFreeAdapter<Self, Res>
@@ -204,6 +384,21 @@ where
     fn hint(&self) -> Vec<String> {
         self.parser.hint()
     }
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parser.suggestion_details(prefix)
+    }
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.parser.failure_span(input)
+    }
+    fn absent_value(&self) -> Option<Res> {
+        self.parser.absent_value()
+    }
+    fn consumes_rest(&self) -> bool {
+        self.parser.consumes_rest()
+    }
 }
 
 impl<T, Res> ContextFreeArgParser<Res> for Rc<T>
@@ -219,6 +414,21 @@ where
     fn hint(&self) -> Vec<String> {
         self.as_ref().hint()
     }
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.as_ref().suggestion_details(prefix)
+    }
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.as_ref().failure_span(input)
+    }
+    fn absent_value(&self) -> Option<Res> {
+        self.as_ref().absent_value()
+    }
+    fn consumes_rest(&self) -> bool {
+        self.as_ref().consumes_rest()
+    }
 }
 
 impl<T, Res> ContextFreeArgParser<Res> for RefCell<T>
@@ -234,6 +444,21 @@ where
     fn hint(&self) -> Vec<String> {
         self.borrow().hint()
     }
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.borrow().suggestion_details(prefix)
+    }
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.borrow().failure_span(input)
+    }
+    fn absent_value(&self) -> Option<Res> {
+        self.borrow().absent_value()
+    }
+    fn consumes_rest(&self) -> bool {
+        self.borrow().consumes_rest()
+    }
 }
 
 /// Generates "context-sensitive" argument parser traits - ones that consider
@@ -249,7 +474,8 @@ macro_rules! define_arg_parser {
     ($name:ident,
      { $( $arg_name:ident : $arg_type:ident ),* $(,)* },
      $res:ident,
-     $map_name:ident, $mapped_res:ident
+     $map_name:ident, $mapped_res:ident,
+     $try_map_name:ident, $validate_name:ident
      $(,)*
     ) => {
         pub trait $name<$( $arg_type, )* $res> {
@@ -259,6 +485,35 @@ macro_rules! define_arg_parser {
                 -> Vec<String>;
             fn hint(&self, $( $arg_name: &$arg_type, )*) -> Vec<String>;
 
+            /// Details for some of the values `suggestion()` returns for the
+            /// same `prefix`.  Most parsers have nothing to add.
+            fn suggestion_details(&self, $( _: &$arg_type, )* _prefix: &str)
+                -> Vec<(String, SuggestionDetails)>
+            {
+                vec![]
+            }
+
+            /// Part of `input` that caused `parse()` to fail, when the parser
+            /// can point at it.  `None` means that the whole argument is at
+            /// fault.
+            fn failure_span(&self, $( _: &$arg_type, )* _input: &str)
+                -> Option<Range<usize>>
+            {
+                None
+            }
+
+            /// Value to use when the argument is not present in the input at
+            /// all.  `None` means that the argument is required.
+            fn absent_value(&self, $( _: &$arg_type, )*) -> Option<$res> {
+                None
+            }
+
+            /// When `true`, the command parser gives this parser all of the
+            /// remaining input, instead of a single word.
+            fn consumes_rest(&self, $( _: &$arg_type, )*) -> bool {
+                false
+            }
+
             /// Creates a new parser that maps the result of the current parser
             /// using a function.
             fn map<F, $mapped_res>(self, f: F)
@@ -270,6 +525,32 @@ macro_rules! define_arg_parser {
                 $map_name::new(self, f)
             }
 
+            /// Creates a new parser that maps the result of the current parser
+            /// using a function that may reject the value.  An `Err` is
+            /// reported as a parse failure, with the error as the reason.  An
+            /// absent value that `f` rejects makes the argument required.
+            fn try_map<F, $mapped_res>(self, f: F)
+                -> $try_map_name<$( $arg_type, )* $res, $mapped_res, Self, F>
+            where
+                F: Fn($( &$arg_type, )* $res) -> Result<$mapped_res, String>,
+                Self: Sized,
+            {
+                $try_map_name::new(self, f)
+            }
+
+            /// Creates a new parser that rejects values that do not satisfy
+            /// `pred`, using `message` as the failure reason.  Absent values
+            /// are not checked.
+            fn validate<P, Message>(self, pred: P, message: Message)
+                -> $validate_name<$( $arg_type, )* $res, Self, P>
+            where
+                P: Fn($( &$arg_type, )* &$res) -> bool,
+                Message: ToString,
+                Self: Sized,
+            {
+                $validate_name::new(self, pred, message.to_string())
+            }
+
             /// It is not uncommon to box parsers, in particular when we want to
             /// put parsers of different types into a vector.  This method helps
             /// to remove some of the syntactic noise.
@@ -301,6 +582,26 @@ macro_rules! define_arg_parser {
             fn hint(&self, $( _: &$arg_type, )*) -> Vec<String> {
                 self.parser.hint()
             }
+
+            fn suggestion_details(&self, $( _: &$arg_type, )* prefix: &str)
+                -> Vec<(String, SuggestionDetails)>
+            {
+                self.parser.suggestion_details(prefix)
+            }
+
+            fn failure_span(&self, $( _: &$arg_type, )* input: &str)
+                -> Option<Range<usize>>
+            {
+                self.parser.failure_span(input)
+            }
+
+            fn absent_value(&self, $( _: &$arg_type, )*) -> Option<$res> {
+                self.parser.absent_value()
+            }
+
+            fn consumes_rest(&self, $( _: &$arg_type, )*) -> bool {
+                self.parser.consumes_rest()
+            }
         }
 
         impl<T, $( $arg_type, )* $res> $name<$( $arg_type, )* $res> for Box<T>
@@ -323,6 +624,32 @@ macro_rules! define_arg_parser {
             fn hint(&self, $( _: &$arg_type, )*) -> Vec<String> {
                 <T as ContextFreeArgParser<$res>>::hint(self.deref())
             }
+
+            fn suggestion_details(&self, $( _: &$arg_type, )* prefix: &str)
+                -> Vec<(String, SuggestionDetails)>
+            {
+                <T as ContextFreeArgParser<$res>>::suggestion_details(
+                    self.deref(),
+                    prefix,
+                )
+            }
+
+            fn failure_span(&self, $( _: &$arg_type, )* input: &str)
+                -> Option<Range<usize>>
+            {
+                <T as ContextFreeArgParser<$res>>::failure_span(
+                    self.deref(),
+                    input,
+                )
+            }
+
+            fn absent_value(&self, $( _: &$arg_type, )*) -> Option<$res> {
+                <T as ContextFreeArgParser<$res>>::absent_value(self.deref())
+            }
+
+            fn consumes_rest(&self, $( _: &$arg_type, )*) -> bool {
+                <T as ContextFreeArgParser<$res>>::consumes_rest(self.deref())
+            }
         }
 
         impl<T, $( $arg_type, )* $res> $name<$( $arg_type, )* $res> for Rc<T>
@@ -344,6 +671,32 @@ macro_rules! define_arg_parser {
             fn hint(&self, $( $arg_name: &$arg_type, )*) -> Vec<String> {
                 self.as_ref().hint($( $arg_name, )*)
             }
+
+            fn suggestion_details(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                prefix: &str,
+            ) -> Vec<(String, SuggestionDetails)> {
+                self.as_ref().suggestion_details($( $arg_name, )* prefix)
+            }
+
+            fn failure_span(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                input: &str,
+            ) -> Option<Range<usize>> {
+                self.as_ref().failure_span($( $arg_name, )* input)
+            }
+
+            fn absent_value(&self, $( $arg_name: &$arg_type, )*)
+                -> Option<$res>
+            {
+                self.as_ref().absent_value($( $arg_name, )*)
+            }
+
+            fn consumes_rest(&self, $( $arg_name: &$arg_type, )*) -> bool {
+                self.as_ref().consumes_rest($( $arg_name, )*)
+            }
         }
 
         impl<T, $( $arg_type, )* $res> $name<$( $arg_type, )* $res> for
@@ -366,6 +719,32 @@ macro_rules! define_arg_parser {
             fn hint(&self, $( $arg_name: &$arg_type, )*) -> Vec<String> {
                 self.borrow().hint($( $arg_name, )*)
             }
+
+            fn suggestion_details(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                prefix: &str,
+            ) -> Vec<(String, SuggestionDetails)> {
+                self.borrow().suggestion_details($( $arg_name, )* prefix)
+            }
+
+            fn failure_span(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                input: &str,
+            ) -> Option<Range<usize>> {
+                self.borrow().failure_span($( $arg_name, )* input)
+            }
+
+            fn absent_value(&self, $( $arg_name: &$arg_type, )*)
+                -> Option<$res>
+            {
+                self.borrow().absent_value($( $arg_name, )*)
+            }
+
+            fn consumes_rest(&self, $( $arg_name: &$arg_type, )*) -> bool {
+                self.borrow().consumes_rest($( $arg_name, )*)
+            }
         }
     };
 }
@@ -377,6 +756,7 @@ define_arg_parser!(
     { res1: Res1, },
     Res2,
     Arg2Map, Res2B,
+    Arg2TryMap, Arg2Validate,
 );
 
 // define_arg_parser!(
@@ -384,4 +764,5 @@ define_arg_parser!(
 //     { res1: Res1, res2: Res2, },
 //     Res3,
 //     Arg3Map, Res3B,
+//     Arg3TryMap, Arg3Validate,
 // );
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{Arg2Parser, ArgParseRes, ContextFreeArgParser};
+use super::{Arg2Parser, ArgParseRes, ContextFreeArgParser, SuggestionDetails};
 
 /// This parser combines several context free argument parsers, that all return
 /// the same result type, trying them one by one, in order.  It is designed to
@@ -22,7 +22,11 @@ use super::{Arg2Parser, ArgParseRes, ContextFreeArgParser};
 /// parser error is the error generated by the parser that managed to parse the
 /// most of the input.
 ///
-/// Hints, if any, are combined from all the parsers.
+/// Hints, if any, are combined from all the parsers.  When the argument is
+/// absent, the first parser that provides an [`absent_value()`] is used.
+/// Alternatives consume the rest of the input only if all of them do.
+///
+/// [`absent_value()`]: ContextFreeArgParser::absent_value
 pub struct ContextFreeAlternativesArgParser<Res> {
     parsers: Vec<Box<dyn ContextFreeArgParser<Res>>>,
 }
@@ -75,6 +79,25 @@ impl<Res> ContextFreeArgParser<Res> for ContextFreeAlternativesArgParser<Res> {
             .flat_map(|parser| parser.hint())
             .collect()
     }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parsers
+            .iter()
+            .flat_map(|parser| parser.suggestion_details(prefix))
+            .collect()
+    }
+
+    /// The first alternative that could be absent provides the value.
+    fn absent_value(&self) -> Option<Res> {
+        self.parsers.iter().find_map(|parser| parser.absent_value())
+    }
+
+    fn consumes_rest(&self) -> bool {
+        self.parsers.iter().all(|parser| parser.consumes_rest())
+    }
 }
 
 /// Generates "context-sensitive" alternatives argument parser - similar to
@@ -150,6 +173,29 @@ macro_rules! define_alternatives_arg_parser {
                     .flat_map(|parser| parser.hint($( $arg_name, )*))
                     .collect()
             }
+
+            fn suggestion_details(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                prefix: &str,
+            ) -> Vec<(String, SuggestionDetails)> {
+                self.parsers.iter()
+                    .flat_map(|parser|
+                        parser.suggestion_details($( $arg_name, )* prefix))
+                    .collect()
+            }
+
+            fn absent_value(&self, $( $arg_name: &$arg_type, )*)
+                -> Option<$res>
+            {
+                self.parsers.iter()
+                    .find_map(|parser| parser.absent_value($( $arg_name, )*))
+            }
+
+            fn consumes_rest(&self, $( $arg_name: &$arg_type, )*) -> bool {
+                self.parsers.iter()
+                    .all(|parser| parser.consumes_rest($( $arg_name, )*))
+            }
         }
     }
 }
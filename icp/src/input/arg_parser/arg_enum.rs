@@ -0,0 +1,286 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for enums, where every variant is represented by a keyword.
+//!
+//! Enums implement [`ArgEnum`] to describe their keywords.  Normally, the
+//! implementation is generated by `#[derive(ArgEnum)]` from the `icp-derive`
+//! crate:
+//!
+//! ```ignore
+//! #[derive(ArgEnum, PartialEq, Clone, Debug)]
+//! enum Duplex {
+//!     /// Both sides transmit at the same time.
+//!     Full,
+//!     /// Sides take turns transmitting.
+//!     #[arg(alias = "hd")]
+//!     Half,
+//!     #[arg(rename = "auto", hint = "auto (negotiate)")]
+//!     Negotiate,
+//! }
+//!
+//! let parser = Duplex::arg_parser();
+//! ```
+
+use std::collections::HashSet;
+use std::iter::once;
+
+use super::keyword_set::{keyword_set_with_hint, KeywordSetArgParser};
+use super::map::ContextFreeMap;
+use super::ContextFreeArgParser;
+
+/// Describes one variant of an [`ArgEnum`].
+#[derive(PartialEq, Clone, Debug)]
+pub struct ArgEnumVariant<T> {
+    pub value: T,
+    /// Keyword that is suggested for this variant.
+    pub keyword: &'static str,
+    /// Additional keywords that are accepted for this variant, but are not
+    /// suggested.
+    pub aliases: &'static [&'static str],
+    /// Shown in the hint in place of the keyword, when set.
+    pub hint: Option<&'static str>,
+    pub description: Option<&'static str>,
+}
+
+/// An enum that could be parsed from a set of keywords.
+pub trait ArgEnum: Clone + Sized + 'static {
+    /// All the variants, in the order they should be suggested.
+    fn variants() -> Vec<ArgEnumVariant<Self>>;
+
+    /// Replaces the hint that is built from the individual variants, when
+    /// set.
+    fn hint() -> Option<&'static str> {
+        None
+    }
+
+    /// Constructs a parser for this enum.  Same as [`arg_enum()`].
+    fn arg_parser() -> ArgEnumArgParser<Self> {
+        arg_enum()
+    }
+}
+
+/// A [`KeywordSetArgParser`] that holds the variant keywords, aliases and
+/// descriptions, mapped into the variant values.
+pub type ArgEnumArgParser<T> =
+    ContextFreeMap<String, T, KeywordSetArgParser, Box<dyn Fn(String) -> T>>;
+
+/// Accepts the keywords of `T`, producing the corresponding variant.
+pub fn arg_enum<T>() -> ArgEnumArgParser<T>
+where
+    T: ArgEnum,
+{
+    let variants = T::variants();
+    if variants.is_empty() {
+        panic!("`T::variants()` should not be empty");
+    }
+
+    let mut spellings = HashSet::new();
+    for spelling in variants
+        .iter()
+        .flat_map(|v| once(v.keyword).chain(v.aliases.iter().copied()))
+    {
+        if !spellings.insert(spelling) {
+            panic!(
+                "`T::variants()` should not repeat keywords or aliases.\n\
+                 Repeated: '{}'",
+                spelling
+            );
+        }
+    }
+
+    let hints = match T::hint() {
+        Some(hint) => vec![hint],
+        None => variants
+            .iter()
+            .map(|v| v.hint.unwrap_or(v.keyword))
+            .collect(),
+    };
+
+    let mut keywords =
+        keyword_set_with_hint(variants.iter().map(|v| v.keyword), hints);
+    for variant in &variants {
+        keywords = keywords.with_aliases(variant.keyword, variant.aliases);
+        if let Some(description) = variant.description {
+            keywords = keywords.with_description(variant.keyword, description);
+        }
+    }
+
+    keywords.map(Box::new(move |keyword: String| {
+        variants
+            .iter()
+            .find(|v| v.keyword == keyword)
+            .map(|v| v.value.clone())
+            // `keywords` only produces keywords from `variants`.
+            .unwrap()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arg_enum, ArgEnum, ArgEnumVariant};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{ContextFreeArgParser, SuggestionDetails};
+
+    #[derive(PartialEq, Clone, Debug)]
+    enum Duplex {
+        Full,
+        Half,
+        Negotiate,
+    }
+
+    impl ArgEnum for Duplex {
+        fn variants() -> Vec<ArgEnumVariant<Self>> {
+            vec![
+                ArgEnumVariant {
+                    value: Duplex::Full,
+                    keyword: "full",
+                    aliases: &[],
+                    hint: None,
+                    description: Some("Both sides transmit at the same time."),
+                },
+                ArgEnumVariant {
+                    value: Duplex::Half,
+                    keyword: "half",
+                    aliases: &["hd"],
+                    hint: None,
+                    description: None,
+                },
+                ArgEnumVariant {
+                    value: Duplex::Negotiate,
+                    keyword: "auto",
+                    aliases: &[],
+                    hint: Some("auto (negotiate)"),
+                    description: None,
+                },
+            ]
+        }
+    }
+
+    #[test]
+    fn keywords() {
+        let parser = Duplex::arg_parser();
+        let expected_hint = &["full", "half", "auto (negotiate)"];
+
+        assert_eq!(
+            parser.suggestion_details("f"),
+            vec![(
+                "full".to_string(),
+                SuggestionDetails {
+                    description: Some(
+                        "Both sides transmit at the same time.".to_string()
+                    ),
+                    ..SuggestionDetails::default()
+                }
+            )]
+        );
+        assert_eq!(parser.suggestion_details("h"), vec![]);
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("full", Duplex::Full);
+        check_parse("half", Duplex::Half);
+        check_parse("hd", Duplex::Half);
+        check_parse("auto", Duplex::Negotiate);
+
+        check_failure("", 0, expected_hint);
+        check_failure("ful", 3, expected_hint);
+        check_failure("hdx", 2, expected_hint);
+        check_failure("negotiate", 0, expected_hint);
+
+        check_suggestions("", &["full", "half", "auto"]);
+        check_suggestions("h", &["half"]);
+        check_suggestions("hd", &[]);
+        check_suggestions("full", &[]);
+    }
+
+    #[test]
+    fn custom_hint() {
+        #[derive(PartialEq, Clone, Debug)]
+        enum Level {
+            Low,
+            High,
+        }
+
+        impl ArgEnum for Level {
+            fn variants() -> Vec<ArgEnumVariant<Self>> {
+                vec![
+                    ArgEnumVariant {
+                        value: Level::Low,
+                        keyword: "low",
+                        aliases: &[],
+                        hint: None,
+                        description: None,
+                    },
+                    ArgEnumVariant {
+                        value: Level::High,
+                        keyword: "high",
+                        aliases: &[],
+                        hint: None,
+                        description: None,
+                    },
+                ]
+            }
+
+            fn hint() -> Option<&'static str> {
+                Some("<level>")
+            }
+        }
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", arg_enum::<Level>());
+
+        check_hint(&["<level>"]);
+        check_parse("high", Level::High);
+        check_failure("lo", 2, &["<level>"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "should not repeat keywords or aliases.\n\
+                               Repeated: 'half'")]
+    fn repeated_keyword() {
+        #[derive(PartialEq, Clone, Debug)]
+        enum Duplex {
+            Full,
+            Half,
+        }
+
+        impl ArgEnum for Duplex {
+            fn variants() -> Vec<ArgEnumVariant<Self>> {
+                vec![
+                    ArgEnumVariant {
+                        value: Duplex::Full,
+                        keyword: "full",
+                        aliases: &["half"],
+                        hint: None,
+                        description: None,
+                    },
+                    ArgEnumVariant {
+                        value: Duplex::Half,
+                        keyword: "half",
+                        aliases: &[],
+                        hint: None,
+                        description: None,
+                    },
+                ]
+            }
+        }
+
+        arg_enum::<Duplex>();
+    }
+}
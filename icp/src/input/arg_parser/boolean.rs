@@ -0,0 +1,222 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::string::ToString;
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+use crate::input::common_prefix::common_prefix_len_by;
+use crate::input::fuzzy_match::chars_eq;
+
+/// Parses on/off style arguments into a `bool`.  Accepts any spelling from a
+/// list of `(true, false)` synonym pairs.  The first pair holds the canonical
+/// spellings - those are used in hints and are suggested when nothing has been
+/// typed yet.
+#[derive(PartialEq, Clone, Debug)]
+pub struct BoolArgParser {
+    synonyms: Vec<(String, String)>,
+    case_insensitive: bool,
+}
+
+impl BoolArgParser {
+    fn new(synonyms: Vec<(String, String)>) -> Self {
+        if synonyms.is_empty() {
+            panic!("`synonyms` should not be empty");
+        }
+
+        Self {
+            synonyms,
+            case_insensitive: false,
+        }
+    }
+
+    /// Makes the parser ignore case, so that `ON`, `On` and `on` are all
+    /// accepted.
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    fn spellings(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.synonyms.iter().flat_map(|(on, off)| {
+            vec![(on.as_str(), true), (off.as_str(), false)].into_iter()
+        })
+    }
+
+    fn normalize(&self, s: &str) -> String {
+        if self.case_insensitive {
+            s.to_lowercase()
+        } else {
+            s.to_string()
+        }
+    }
+}
+
+/// Accepts `on`/`off`, `true`/`false`, `yes`/`no`, `1`/`0` and
+/// `enable`/`disable`, with `on`/`off` been the canonical spelling.
+pub fn boolean() -> BoolArgParser {
+    boolean_with_synonyms(vec![
+        ("on", "off"),
+        ("true", "false"),
+        ("yes", "no"),
+        ("1", "0"),
+        ("enable", "disable"),
+    ])
+}
+
+/// Accepts spellings from the provided `(true, false)` pairs.  The first pair
+/// is considered canonical.
+pub fn boolean_with_synonyms<On, Off, Synonyms>(
+    synonyms: Synonyms,
+) -> BoolArgParser
+where
+    On: ToString,
+    Off: ToString,
+    Synonyms: IntoIterator<Item = (On, Off)>,
+{
+    let synonyms = synonyms
+        .into_iter()
+        .map(|(on, off)| (on.to_string(), off.to_string()))
+        .collect::<Vec<_>>();
+    BoolArgParser::new(synonyms)
+}
+
+impl ContextFreeArgParser<bool> for BoolArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<bool> {
+        let normalized = self.normalize(input);
+
+        for (spelling, value) in self.spellings() {
+            if normalized == self.normalize(spelling) {
+                return ArgParseRes::Parsed(value);
+            }
+        }
+
+        // Compared on the original `input`, as case conversion may change
+        // lengths of some characters.
+        let longest_match = self
+            .spellings()
+            .map(|(spelling, _)| {
+                common_prefix_len_by(input, spelling, |c1, c2| {
+                    chars_eq(c1, c2, self.case_insensitive)
+                })
+            })
+            .max()
+            .unwrap_or(0);
+
+        ArgParseRes::Failed {
+            parsed_up_to: longest_match,
+            reason: self.hint(),
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        if prefix.is_empty() {
+            return self.hint();
+        }
+
+        let prefix = self.normalize(prefix);
+        self.spellings()
+            .map(|(spelling, _)| spelling)
+            .filter(|spelling| {
+                let spelling = self.normalize(spelling);
+                spelling.starts_with(&prefix) && spelling.len() > prefix.len()
+            })
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let (on, off) = &self.synonyms[0];
+        vec![on.clone(), off.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boolean, boolean_with_synonyms};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn default_synonyms() {
+        let parser = boolean();
+        let expected_hint = &["on", "off"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("on", true);
+        check_parse("off", false);
+        check_parse("true", true);
+        check_parse("false", false);
+        check_parse("yes", true);
+        check_parse("no", false);
+        check_parse("1", true);
+        check_parse("0", false);
+        check_parse("enable", true);
+        check_parse("disable", false);
+
+        check_failure("", 0, expected_hint);
+        check_failure("ON", 0, expected_hint);
+        check_failure("o", 1, expected_hint);
+        check_failure("onn", 2, expected_hint);
+        check_failure("ena", 3, expected_hint);
+        check_failure("2", 0, expected_hint);
+
+        check_suggestions("", &["on", "off"]);
+        check_suggestions("o", &["on", "off"]);
+        check_suggestions("of", &["off"]);
+        check_suggestions("off", &[]);
+        check_suggestions("y", &["yes"]);
+        check_suggestions("e", &["enable"]);
+        check_suggestions("x", &[]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let parser =
+            boolean_with_synonyms(vec![("enabled", "disabled"), ("Y", "N")])
+                .case_insensitive();
+        let expected_hint = &["enabled", "disabled"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("enabled", true);
+        check_parse("ENABLED", true);
+        check_parse("Disabled", false);
+        check_parse("y", true);
+        check_parse("N", false);
+
+        check_failure("on", 0, expected_hint);
+        check_failure("EnAb", 4, expected_hint);
+
+        check_suggestions("", &["enabled", "disabled"]);
+        check_suggestions("D", &["disabled"]);
+        check_suggestions("n", &[]);
+
+        let (_, _, check_parse, check_failure) = build_cf_parse_checkers(
+            "parser",
+            boolean_with_synonyms(vec![("keep", "kill")]).case_insensitive(),
+        );
+
+        // Lower case of the Kelvin sign is `k`, but it is 3 bytes long.
+        check_parse("\u{212A}eep", true);
+        check_failure("\u{212A}eap", 4, &["keep", "kill"]);
+    }
+}
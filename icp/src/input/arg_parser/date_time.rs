@@ -0,0 +1,601 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for points in time, in absolute and relative forms.
+//!
+//! Accepted forms are:
+//!
+//! * ISO 8601 timestamps: `2021-03-04T10:20`, `2021-03-04T10:20:30.5Z` or
+//!   `2021-03-04T10:20:30+02:00`.
+//! * Dates: `2021-03-04`, meaning the midnight of that day.
+//! * Times of day: `10:20` or `10:20:30`, meaning that time today.
+//! * Durations relative to the current time: `-15m`, `+1h30m`.  See
+//!   [`super::duration`] for the duration syntax.
+//! * Keywords: `now`, `today` and `yesterday`.  The last two mean midnight of
+//!   the corresponding day.
+//!
+//! Unless an explicit offset is given, all times are in UTC.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::duration::duration;
+use super::{ArgParseRes, ContextFreeArgParser};
+
+use crate::input::common_prefix::common_prefix_len;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+const KEYWORDS: &[&str] = &["now", "today", "yesterday"];
+
+/// Layouts of the absolute forms, used to suggest the next separator.  `d`
+/// stands for a digit.
+const TIMESTAMP_LAYOUT: &str = "dddd-dd-ddTdd:dd:dd";
+const TIME_OF_DAY_LAYOUT: &str = "dd:dd:dd";
+
+/// Source of the current time, used to resolve relative forms.  Could be
+/// replaced in tests, to make results predictable.
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock that returns the actual current time.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A fixed point in time could be used as a clock that never changes.
+impl Clock for SystemTime {
+    fn now(&self) -> SystemTime {
+        *self
+    }
+}
+
+/// Parses a point in time into a `SystemTime`.  See the module documentation
+/// for the accepted forms.
+#[derive(PartialEq, Clone, Debug)]
+pub struct DateTimeArgParser<C>
+where
+    C: Clock,
+{
+    clock: C,
+    name: Option<String>,
+}
+
+/// Accepts any of the supported forms, relative to the system clock.
+pub fn date_time() -> DateTimeArgParser<SystemClock> {
+    DateTimeArgParser {
+        clock: SystemClock,
+        name: None,
+    }
+}
+
+/// Names the argument.  Name will be included in the hint.
+pub fn date_time_with_name<Name>(name: Name) -> DateTimeArgParser<SystemClock>
+where
+    Name: Into<String>,
+{
+    DateTimeArgParser {
+        clock: SystemClock,
+        name: Some(name.into()),
+    }
+}
+
+impl<C> DateTimeArgParser<C>
+where
+    C: Clock,
+{
+    /// Resolves relative forms against `clock`, rather than the system clock.
+    pub fn with_clock<NewClock>(
+        self,
+        clock: NewClock,
+    ) -> DateTimeArgParser<NewClock>
+    where
+        NewClock: Clock,
+    {
+        DateTimeArgParser {
+            clock,
+            name: self.name,
+        }
+    }
+}
+
+/// Failure to parse a time.  Positions are relative to the whole argument.
+struct TimeError {
+    parsed_up_to: usize,
+    /// `None` means the parser hint should be used as the failure reason.
+    reason: Option<String>,
+}
+
+impl TimeError {
+    fn hint(parsed_up_to: usize) -> Self {
+        Self {
+            parsed_up_to,
+            reason: None,
+        }
+    }
+
+    fn new<Reason>(parsed_up_to: usize, reason: Reason) -> Self
+    where
+        Reason: Into<String>,
+    {
+        Self {
+            parsed_up_to,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Number of days between 1970-01-01 and the given date in the proleptic
+/// Gregorian calendar.  See
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let leap = (year.is_multiple_of(4) && !year.is_multiple_of(100))
+        || year.is_multiple_of(400);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn unix_secs(t: SystemTime) -> i64 {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        // Round towards negative infinity, so that the start of the day is
+        // computed correctly.
+        Err(err) => {
+            let d = err.duration();
+            -(d.as_secs() as i64) - if d.subsec_nanos() > 0 { 1 } else { 0 }
+        }
+    }
+}
+
+fn from_unix(secs: i64, nanos: u32) -> Option<SystemTime> {
+    let t = if secs >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))?
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))?
+    };
+    t.checked_add(Duration::from_nanos(nanos.into()))
+}
+
+fn start_of_day(t: SystemTime) -> i64 {
+    unix_secs(t).div_euclid(SECS_PER_DAY) * SECS_PER_DAY
+}
+
+/// Walks over an absolute time, one component at a time.
+struct Scanner<'input> {
+    input: &'input [u8],
+    pos: usize,
+}
+
+impl<'input> Scanner<'input> {
+    fn at_end(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    fn next_is(&self, c: u8) -> bool {
+        self.input.get(self.pos) == Some(&c)
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), TimeError> {
+        if !self.next_is(c) {
+            return Err(TimeError::hint(self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Reads a number that has exactly `digits` digits, and checks that it is
+    /// within `[min, max]`.  `what` is used in the error message.
+    fn number(
+        &mut self,
+        digits: usize,
+        min: u32,
+        max: u32,
+        what: &str,
+    ) -> Result<u32, TimeError> {
+        let start = self.pos;
+        let mut value = 0;
+        for i in start..start + digits {
+            match self.input.get(i) {
+                Some(c) if c.is_ascii_digit() => {
+                    value = value * 10 + u32::from(c - b'0');
+                }
+                _ => return Err(TimeError::hint(i)),
+            }
+        }
+        self.pos += digits;
+
+        if value < min || value > max {
+            return Err(TimeError::new(
+                start,
+                format!("{} must be within {}-{}", what, min, max),
+            ));
+        }
+
+        Ok(value)
+    }
+
+    /// Reads `YYYY-MM-DD`, returning the number of days since the epoch.
+    fn date(&mut self) -> Result<i64, TimeError> {
+        let year = self.number(4, 1, 9999, "year")?;
+        self.expect(b'-')?;
+        let month = self.number(2, 1, 12, "month")?;
+        self.expect(b'-')?;
+        let day = self.number(2, 1, days_in_month(year, month), "day")?;
+
+        Ok(days_from_civil(year.into(), month.into(), day.into()))
+    }
+
+    /// Reads `HH:MM[:SS[.fraction]]`, returning seconds since midnight and
+    /// nanoseconds.
+    fn time_of_day(&mut self) -> Result<(i64, u32), TimeError> {
+        let hour = self.number(2, 0, 23, "hour")?;
+        self.expect(b':')?;
+        let minute = self.number(2, 0, 59, "minute")?;
+
+        let mut second = 0;
+        let mut nanos = 0;
+        if self.next_is(b':') {
+            self.pos += 1;
+            second = self.number(2, 0, 59, "second")?;
+
+            if self.next_is(b'.') {
+                self.pos += 1;
+                let start = self.pos;
+                let mut scale = 100_000_000;
+                while let Some(c) = self.input.get(self.pos) {
+                    if !c.is_ascii_digit() || self.pos - start == 9 {
+                        break;
+                    }
+                    nanos += u32::from(c - b'0') * scale;
+                    scale /= 10;
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(TimeError::hint(self.pos));
+                }
+            }
+        }
+
+        let secs =
+            i64::from(hour) * 3600 + i64::from(minute) * 60 + i64::from(second);
+        Ok((secs, nanos))
+    }
+
+    /// Reads an optional `Z` or `+HH:MM`/`-HH:MM` suffix, returning the
+    /// offset from UTC in seconds.
+    fn offset(&mut self) -> Result<i64, TimeError> {
+        let sign = match self.input.get(self.pos) {
+            Some(b'Z') => {
+                self.pos += 1;
+                return Ok(0);
+            }
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            _ => return Ok(0),
+        };
+        self.pos += 1;
+
+        let hours = self.number(2, 0, 23, "offset hours")?;
+        self.expect(b':')?;
+        let minutes = self.number(2, 0, 59, "offset minutes")?;
+
+        Ok(sign * (i64::from(hours) * 3600 + i64::from(minutes) * 60))
+    }
+
+    fn finish(&self) -> Result<(), TimeError> {
+        if self.at_end() {
+            Ok(())
+        } else {
+            Err(TimeError::hint(self.pos))
+        }
+    }
+}
+
+impl<C> DateTimeArgParser<C>
+where
+    C: Clock,
+{
+    fn parse_time(&self, input: &str) -> Result<SystemTime, TimeError> {
+        let now = self.clock.now();
+
+        match input {
+            "now" => return Ok(now),
+            "today" => return self.to_time(input, start_of_day(now), 0),
+            "yesterday" => {
+                return self.to_time(input, start_of_day(now) - SECS_PER_DAY, 0)
+            }
+            _ => (),
+        }
+
+        let mut scanner = Scanner {
+            input: input.as_bytes(),
+            pos: 0,
+        };
+
+        match input.as_bytes() {
+            [b'+', ..] | [b'-', ..] => self.parse_relative(input, now),
+            [_, _, b':', ..] => {
+                let (secs, nanos) = scanner.time_of_day()?;
+                let offset = scanner.offset()?;
+                scanner.finish()?;
+                self.to_time(input, start_of_day(now) + secs - offset, nanos)
+            }
+            [c, ..] if c.is_ascii_digit() => {
+                let days = scanner.date()?;
+                let (secs, nanos, offset) = if scanner.next_is(b'T') {
+                    scanner.pos += 1;
+                    let (secs, nanos) = scanner.time_of_day()?;
+                    (secs, nanos, scanner.offset()?)
+                } else {
+                    (0, 0, 0)
+                };
+                scanner.finish()?;
+                self.to_time(input, days * SECS_PER_DAY + secs - offset, nanos)
+            }
+            _ => {
+                let longest_match = KEYWORDS
+                    .iter()
+                    .map(|k| common_prefix_len(input, k))
+                    .max()
+                    .unwrap_or(0);
+                Err(TimeError::hint(longest_match))
+            }
+        }
+    }
+
+    fn parse_relative(
+        &self,
+        input: &str,
+        now: SystemTime,
+    ) -> Result<SystemTime, TimeError> {
+        let offset = match duration().parse(&input[1..]) {
+            ArgParseRes::Parsed(offset) => offset,
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            } => {
+                return Err(TimeError {
+                    parsed_up_to: parsed_up_to + 1,
+                    reason: Some(reason.join(" | ")),
+                })
+            }
+        };
+
+        let res = if input.starts_with('-') {
+            now.checked_sub(offset)
+        } else {
+            now.checked_add(offset)
+        };
+        res.ok_or_else(|| TimeError::new(input.len(), "time is out of range"))
+    }
+
+    fn to_time(
+        &self,
+        input: &str,
+        secs: i64,
+        nanos: u32,
+    ) -> Result<SystemTime, TimeError> {
+        from_unix(secs, nanos)
+            .ok_or_else(|| TimeError::new(input.len(), "time is out of range"))
+    }
+}
+
+/// When `prefix` follows `layout` up to a boundary between two components,
+/// returns `prefix` extended with the separator that comes next.
+fn next_separator(prefix: &str, layout: &str) -> Option<String> {
+    let follows_layout =
+        prefix.bytes().zip(layout.bytes()).all(|(c, l)| match l {
+            b'd' => c.is_ascii_digit(),
+            _ => c == l,
+        });
+    if prefix.is_empty() || !follows_layout {
+        return None;
+    }
+
+    match layout.as_bytes().get(prefix.len()) {
+        Some(b'd') | None => None,
+        Some(separator) => Some(format!("{}{}", prefix, *separator as char)),
+    }
+}
+
+impl<C> ContextFreeArgParser<SystemTime> for DateTimeArgParser<C>
+where
+    C: Clock,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<SystemTime> {
+        match self.parse_time(input) {
+            Ok(time) => ArgParseRes::Parsed(time),
+            Err(TimeError {
+                parsed_up_to,
+                reason,
+            }) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: match reason {
+                    Some(reason) => vec![reason],
+                    None => self.hint(),
+                },
+            },
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        let mut res = KEYWORDS
+            .iter()
+            .filter(|k| k.starts_with(prefix) && k.len() > prefix.len())
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(sign) = prefix.get(0..1).filter(|s| *s == "+" || *s == "-")
+        {
+            res.extend(
+                duration()
+                    .suggestion(&prefix[1..])
+                    .into_iter()
+                    .map(|s| format!("{}{}", sign, s)),
+            );
+        }
+
+        res.extend(next_separator(prefix, TIMESTAMP_LAYOUT));
+        res.extend(next_separator(prefix, TIME_OF_DAY_LAYOUT));
+
+        res
+    }
+
+    fn hint(&self) -> Vec<String> {
+        match &self.name {
+            Some(name) => vec![format!("<{}>", name)],
+            None => vec!["<time>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{date_time, date_time_with_name};
+
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    /// 2021-03-06T03:06:40Z
+    const NOW_SECS: u64 = 1_615_000_000;
+
+    fn at(secs: i64) -> SystemTime {
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+        }
+    }
+
+    fn now() -> SystemTime {
+        at(NOW_SECS as i64)
+    }
+
+    #[test]
+    fn absolute() {
+        let parser = date_time().with_clock(now());
+        let expected_hint = &["<time>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("2021-03-04T10:20:30", at(1_614_853_230));
+        check_parse("2021-03-04T10:20:30Z", at(1_614_853_230));
+        check_parse("2021-03-04T12:20:30+02:00", at(1_614_853_230));
+        check_parse("2021-03-04T09:20:30-01:00", at(1_614_853_230));
+        check_parse("2021-03-04T10:20", at(1_614_853_200));
+        check_parse(
+            "2021-03-04T10:20:30.25",
+            at(1_614_853_230) + Duration::from_millis(250),
+        );
+        check_parse("2021-03-06", at(1_614_988_800));
+        check_parse("2020-02-29T23:59:59", at(1_583_020_799));
+        check_parse("1969-12-31T23:00", at(-3600));
+        check_parse("03:00", at(1_614_999_600));
+        check_parse("03:00:00Z", at(1_614_999_600));
+
+        check_failure("", 0, expected_hint);
+        check_failure("2021", 4, expected_hint);
+        check_failure("2021-3-04", 6, expected_hint);
+        check_failure("2021-13-04", 5, &["month must be within 1-12"]);
+        check_failure("2021-02-29", 8, &["day must be within 1-28"]);
+        check_failure("2021-04-31", 8, &["day must be within 1-30"]);
+        check_failure("0000-01-01", 0, &["year must be within 1-9999"]);
+        check_failure("2021-03-04T", 11, expected_hint);
+        check_failure("2021-03-04T24:00", 11, &["hour must be within 0-23"]);
+        check_failure("2021-03-04T10:60", 14, &["minute must be within 0-59"]);
+        check_failure("2021-03-04T10:20:30.", 20, expected_hint);
+        check_failure("2021-03-04T10:20:30+2", 21, expected_hint);
+        check_failure("2021-03-04T10:20:30x", 19, expected_hint);
+        check_failure("2021-03-04x", 10, expected_hint);
+        check_failure("10:2", 4, expected_hint);
+        check_failure("1o:20", 1, expected_hint);
+    }
+
+    #[test]
+    fn relative() {
+        let parser = date_time().with_clock(now());
+        let expected_hint = &["<time>"];
+
+        let (_check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("now", now());
+        check_parse("today", at(1_614_988_800));
+        check_parse("yesterday", at(1_614_988_800 - 86400));
+        check_parse("-15m", now() - Duration::from_secs(15 * 60));
+        check_parse("+1h30m", now() + Duration::from_secs(90 * 60));
+
+        check_failure("no", 2, expected_hint);
+        check_failure("tomorrow", 2, expected_hint);
+        check_failure("-", 1, &["<duration>"]);
+        check_failure("-15x", 3, &["units: d, h, m, s, ms, us, ns"]);
+    }
+
+    #[test]
+    fn suggestions() {
+        let parser = date_time_with_name("since").with_clock(now());
+
+        let (check_hint, check_suggestions, _check_parse, _check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["<since>"]);
+
+        check_suggestions("", &["now", "today", "yesterday"]);
+        check_suggestions("t", &["today"]);
+        check_suggestions("now", &[]);
+        check_suggestions("2021", &["2021-"]);
+        check_suggestions("2021-03", &["2021-03-"]);
+        check_suggestions("2021-03-04", &["2021-03-04T"]);
+        check_suggestions("2021-03-04T10", &["2021-03-04T10:"]);
+        check_suggestions("2021-03-04T10:20", &["2021-03-04T10:20:"]);
+        check_suggestions("2021-03-04T10:20:30", &[]);
+        check_suggestions("202", &[]);
+        check_suggestions("10", &["10:"]);
+        check_suggestions("10:20", &["10:20:"]);
+        check_suggestions(
+            "-15",
+            &["-15d", "-15h", "-15m", "-15s", "-15ms", "-15us", "-15ns"],
+        );
+        check_suggestions(
+            "-1h3",
+            &["-1h3m", "-1h3s", "-1h3ms", "-1h3us", "-1h3ns"],
+        );
+        check_suggestions("-1é2", &[]);
+        check_suggestions("ü", &[]);
+    }
+}
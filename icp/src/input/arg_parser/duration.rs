@@ -0,0 +1,458 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for durations, such as `250ms`, `2.5s` or `1h30m`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use std::time::Duration;
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// All the supported units, from the largest to the smallest, with their
+/// length in nanoseconds.  Components of a compound duration must use units in
+/// this order.
+const UNITS: &[(&str, u128)] = &[
+    ("d", 24 * 60 * 60 * NANOS_PER_SEC),
+    ("h", 60 * 60 * NANOS_PER_SEC),
+    ("m", 60 * NANOS_PER_SEC),
+    ("s", NANOS_PER_SEC),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// Accepts durations as a sequence of components, each one a number followed
+/// by a unit.  Units are `d`, `h`, `m`, `s`, `ms`, `us` and `ns`.  Components
+/// must be listed from the largest unit to the smallest, and each unit can
+/// only be used once.  Numbers may have a fractional part.  For example:
+/// `1h30m`, `250ms` or `2.5s`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct DurationArgParser {
+    min: Option<Duration>,
+    max: Option<Duration>,
+    name: Option<String>,
+}
+
+/// Accepts any duration.
+pub fn duration() -> DurationArgParser {
+    DurationArgParser {
+        min: None,
+        max: None,
+        name: None,
+    }
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+pub fn duration_for_range(min: Duration, max: Duration) -> DurationArgParser {
+    DurationArgParser {
+        min: Some(min),
+        max: Some(max),
+        name: None,
+    }
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn duration_with_name<Name>(name: Name) -> DurationArgParser
+where
+    Name: Into<String>,
+{
+    DurationArgParser {
+        min: None,
+        max: None,
+        name: Some(name.into()),
+    }
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+/// Also names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn duration_for_range_and_name<Name>(
+    min: Duration,
+    max: Duration,
+    name: Name,
+) -> DurationArgParser
+where
+    Name: Into<String>,
+{
+    DurationArgParser {
+        min: Some(min),
+        max: Some(max),
+        name: Some(name.into()),
+    }
+}
+
+/// Formats a duration using the same syntax [`DurationArgParser`] accepts.
+pub fn format_duration(duration: Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+
+    let mut res = String::new();
+    for (suffix, unit_nanos) in UNITS {
+        let count = nanos / unit_nanos;
+        if count > 0 {
+            res.push_str(&format!("{}{}", count, suffix));
+            nanos %= unit_nanos;
+        }
+    }
+    res
+}
+
+fn units_reason() -> Vec<String> {
+    let suffixes = UNITS.iter().map(|(suffix, _)| *suffix).collect::<Vec<_>>();
+    vec![format!("units: {}", suffixes.join(", "))]
+}
+
+/// Length, in bytes, of the number at the start of `input`, or `0` if `input`
+/// does not start with a number.
+fn number_len(input: &str) -> usize {
+    lazy_static! {
+        static ref NUMBER: Regex =
+            Regex::new(r"^([0-9]+(\.[0-9]*)?|\.[0-9]+)").unwrap();
+    }
+
+    NUMBER.find(input).map_or(0, |m| m.end())
+}
+
+/// Converts `number` expressed in units of `unit_nanos` into nanoseconds.
+fn component_nanos(number: &str, unit_nanos: u128) -> Option<u128> {
+    let (whole, fraction) = match number.find('.') {
+        Some(dot) => (&number[..dot], &number[dot + 1..]),
+        None => (number, ""),
+    };
+
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u128>().ok()?
+    };
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        let fraction = format!("0.{}", fraction).parse::<f64>().ok()?;
+        (fraction * unit_nanos as f64).round() as u128
+    };
+
+    whole.checked_mul(unit_nanos)?.checked_add(fraction)
+}
+
+/// Outcome of a scan of the duration components.
+enum Scan {
+    /// All of the input formed valid components.  Holds the total duration in
+    /// nanoseconds and the index of the last unit used.
+    Complete { nanos: u128, last_unit: usize },
+    /// Input is not a valid duration.  An empty `reason` means that a number
+    /// was expected at `parsed_up_to`.
+    Invalid {
+        parsed_up_to: usize,
+        reason: Vec<String>,
+    },
+}
+
+/// Splits `input` into components and adds them up.
+fn scan(input: &str) -> Scan {
+    let mut pos = 0;
+    let mut nanos = 0u128;
+    let mut last_unit = None;
+
+    while pos < input.len() {
+        let rest = &input[pos..];
+        let number_end = number_len(rest);
+        if number_end == 0 {
+            return Scan::Invalid {
+                parsed_up_to: pos,
+                reason: vec![],
+            };
+        }
+
+        let (number, unit_text) = rest.split_at(number_end);
+        // Longest suffix first, so that `ms` is not mistaken for `m`.
+        let unit = UNITS
+            .iter()
+            .enumerate()
+            .filter(|(_, (suffix, _))| unit_text.starts_with(suffix))
+            .max_by_key(|(_, (suffix, _))| suffix.len());
+        let (unit_i, (suffix, unit_nanos)) = match unit {
+            Some(unit) => unit,
+            None => {
+                let is_partial = !unit_text.is_empty()
+                    && UNITS
+                        .iter()
+                        .any(|(suffix, _)| suffix.starts_with(unit_text));
+                let partial = if is_partial { unit_text.len() } else { 0 };
+                return Scan::Invalid {
+                    parsed_up_to: pos + number_end + partial,
+                    reason: units_reason(),
+                };
+            }
+        };
+
+        if let Some(last_unit) = last_unit {
+            if unit_i <= last_unit {
+                let reason = if unit_i == last_unit {
+                    format!("'{}' can only be used once", suffix)
+                } else {
+                    format!(
+                        "'{}' must come before '{}'",
+                        suffix, UNITS[last_unit].0
+                    )
+                };
+                return Scan::Invalid {
+                    parsed_up_to: pos + number_end,
+                    reason: vec![reason],
+                };
+            }
+        }
+
+        nanos = match component_nanos(number, *unit_nanos)
+            .and_then(|component| nanos.checked_add(component))
+        {
+            Some(nanos) => nanos,
+            None => {
+                return Scan::Invalid {
+                    parsed_up_to: input.len(),
+                    reason: vec!["duration is too long".to_string()],
+                }
+            }
+        };
+
+        last_unit = Some(unit_i);
+        pos += number_end + suffix.len();
+    }
+
+    match last_unit {
+        Some(last_unit) => Scan::Complete { nanos, last_unit },
+        None => Scan::Invalid {
+            parsed_up_to: 0,
+            reason: vec![],
+        },
+    }
+}
+
+impl DurationArgParser {
+    fn check_range(&self, v: Duration, input: &str) -> ArgParseRes<Duration> {
+        let (what, bound) = match (self.min, self.max) {
+            (Some(min), _) if v < min => ("min", min),
+            (_, Some(max)) if v > max => ("max", max),
+            _ => return ArgParseRes::Parsed(v),
+        };
+
+        let bound = format_duration(bound);
+        ArgParseRes::Failed {
+            parsed_up_to: input.len(),
+            reason: match &self.name {
+                Some(name) => vec![format!("{} {}: {}", what, name, bound)],
+                None => vec![format!("{}: {}", what, bound)],
+            },
+        }
+    }
+}
+
+impl ContextFreeArgParser<Duration> for DurationArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<Duration> {
+        match scan(input) {
+            Scan::Complete { nanos, .. } => {
+                let secs = nanos / NANOS_PER_SEC;
+                if secs > u64::MAX as u128 {
+                    return ArgParseRes::Failed {
+                        parsed_up_to: input.len(),
+                        reason: vec!["duration is too long".to_string()],
+                    };
+                }
+                let v =
+                    Duration::new(secs as u64, (nanos % NANOS_PER_SEC) as u32);
+                self.check_range(v, input)
+            }
+            Scan::Invalid {
+                parsed_up_to,
+                reason,
+            } => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: if reason.is_empty() {
+                    self.hint()
+                } else {
+                    reason
+                },
+            },
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        // Suggestions are the unit suffixes that can follow the last number in
+        // the prefix.
+        let number_end =
+            match prefix.rfind(|c: char| c.is_ascii_digit() || c == '.') {
+                Some(i) => i + 1,
+                None => return vec![],
+            };
+        let (head, unit_text) = prefix.split_at(number_end);
+
+        let head_components_end = head
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !(c.is_ascii_digit() || *c == '.'))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let next_unit = match scan(&head[..head_components_end]) {
+            Scan::Complete { last_unit, .. } => last_unit + 1,
+            Scan::Invalid {
+                parsed_up_to: 0, ..
+            } if head_components_end == 0 => 0,
+            Scan::Invalid { .. } => return vec![],
+        };
+
+        UNITS[next_unit..]
+            .iter()
+            .filter(|(suffix, _)| {
+                suffix.starts_with(unit_text) && suffix.len() > unit_text.len()
+            })
+            .map(|(suffix, _)| format!("{}{}", head, suffix))
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let range = match (self.min, self.max) {
+            (Some(min), Some(max)) => Some(format!(
+                "{}-{}",
+                format_duration(min),
+                format_duration(max)
+            )),
+            (Some(min), None) => Some(format!(">={}", format_duration(min))),
+            (None, Some(max)) => Some(format!("<={}", format_duration(max))),
+            (None, None) => None,
+        };
+
+        match (&self.name, range) {
+            (Some(name), Some(range)) => vec![format!("<{}: {}>", name, range)],
+            (Some(name), None) => vec![format!("<{}>", name)],
+            (None, Some(range)) => vec![format!("<{}>", range)],
+            (None, None) => vec!["<duration>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        duration, duration_for_range, duration_for_range_and_name,
+        duration_with_name, format_duration,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0s");
+        assert_eq!(format_duration(Duration::from_millis(250)), "250ms");
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(
+            format_duration(Duration::new(90061, 1_001)),
+            "1d1h1m1s1us1ns"
+        );
+    }
+
+    #[test]
+    fn simple() {
+        let parser = duration();
+        let expected_hint = &["<duration>"];
+        let expected_units = &["units: d, h, m, s, ms, us, ns"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("250ms", Duration::from_millis(250));
+        check_parse("2.5s", Duration::from_millis(2500));
+        check_parse("1h30m", Duration::from_secs(5400));
+        check_parse("1d", Duration::from_secs(86400));
+        check_parse("1m1s1ms", Duration::from_millis(61_001));
+        check_parse(".5us", Duration::from_nanos(500));
+        check_parse("0s", Duration::from_secs(0));
+
+        check_failure("", 0, expected_hint);
+        check_failure("s", 0, expected_hint);
+        check_failure("10", 2, expected_units);
+        check_failure("10x", 2, expected_units);
+        check_failure("10u", 3, expected_units);
+        check_failure("1h30", 4, expected_units);
+        check_failure("1h-30m", 2, expected_hint);
+        check_failure("30m1h", 4, &["'h' must come before 'm'"]);
+        check_failure("1s1s", 3, &["'s' can only be used once"]);
+        check_failure(&("9".repeat(41) + "d"), 42, &["duration is too long"]);
+
+        check_suggestions("", &[]);
+        check_suggestions("h", &[]);
+        check_suggestions("1", &["1d", "1h", "1m", "1s", "1ms", "1us", "1ns"]);
+        check_suggestions("1m", &["1ms"]);
+        check_suggestions("1u", &["1us"]);
+        check_suggestions("1h3", &["1h3m", "1h3s", "1h3ms", "1h3us", "1h3ns"]);
+        check_suggestions(
+            "1h30m1",
+            &["1h30m1s", "1h30m1ms", "1h30m1us", "1h30m1ns"],
+        );
+        check_suggestions("1s3m", &["1s3ms"]);
+        check_suggestions("1s3", &["1s3ms", "1s3us", "1s3ns"]);
+        check_suggestions("1x3", &[]);
+        check_suggestions("1é2", &[]);
+        check_suggestions("é2", &[]);
+        check_suggestions("1µ", &[]);
+    }
+
+    #[test]
+    fn with_name() {
+        let (check_hint, _, check_parse, _) =
+            build_cf_parse_checkers("parser", duration_with_name("interval"));
+
+        check_hint(&["<interval>"]);
+        check_parse("1.5h", Duration::from_secs(5400));
+    }
+
+    #[test]
+    fn with_range() {
+        let parser = duration_for_range_and_name(
+            Duration::from_millis(1),
+            Duration::from_secs(3600),
+            "timeout",
+        );
+        let expected_hint = &["<timeout: 1ms-1h>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("1ms", Duration::from_millis(1));
+        check_parse("59m60s", Duration::from_secs(3600));
+
+        check_failure("999us", 5, &["min timeout: 1ms"]);
+        check_failure("1h1ns", 5, &["max timeout: 1h"]);
+        check_failure("", 0, expected_hint);
+
+        let (check_hint, _, _, check_failure) = build_cf_parse_checkers(
+            "parser",
+            duration_for_range(Duration::from_secs(1), Duration::from_secs(90)),
+        );
+
+        check_hint(&["<1s-1m30s>"]);
+        check_failure("2m", 2, &["max: 1m30s"]);
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keyword parser for sets of keywords that change while the application is
+//! running, like names of attached devices or open sessions.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use super::keyword_set::{keyword_set, keyword_set_with_hint};
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// Source of the current set of keywords for a [`DynamicKeywordSetArgParser`].
+/// Implemented for closures that return a list of keywords.
+pub trait KeywordProvider {
+    fn keywords(&self) -> Vec<String>;
+}
+
+impl<F> KeywordProvider for F
+where
+    F: Fn() -> Vec<String>,
+{
+    fn keywords(&self) -> Vec<String> {
+        self()
+    }
+}
+
+impl KeywordProvider for Box<dyn KeywordProvider> {
+    fn keywords(&self) -> Vec<String> {
+        self.as_ref().keywords()
+    }
+}
+
+/// Accepts one of the keywords returned by a [`KeywordProvider`].  Provider
+/// is queried on every call, unless caching is enabled with
+/// [`DynamicKeywordSetArgParser::with_cache_ttl()`].
+///
+/// Matching is the same as in [`super::keyword_set()`].
+pub struct DynamicKeywordSetArgParser<Provider>
+where
+    Provider: KeywordProvider,
+{
+    provider: Provider,
+    /// When `None`, current keywords are used as the hint.
+    hints: Option<Vec<String>>,
+    cache_ttl: Option<Duration>,
+    cache: RefCell<Option<(Instant, Vec<String>)>>,
+}
+
+/// Hint used when the provider returned no keywords, and no explicit hint
+/// was given.
+const NO_KEYWORDS_HINT: &str = "<none available>";
+
+pub fn dynamic_keyword_set<Provider>(
+    provider: Provider,
+) -> DynamicKeywordSetArgParser<Provider>
+where
+    Provider: KeywordProvider,
+{
+    DynamicKeywordSetArgParser {
+        provider,
+        hints: None,
+        cache_ttl: None,
+        cache: RefCell::new(None),
+    }
+}
+
+pub fn dynamic_keyword_set_with_hint<Provider, Hint, Hints>(
+    provider: Provider,
+    hints: Hints,
+) -> DynamicKeywordSetArgParser<Provider>
+where
+    Provider: KeywordProvider,
+    Hint: ToString,
+    Hints: IntoIterator<Item = Hint>,
+{
+    DynamicKeywordSetArgParser {
+        provider,
+        hints: Some(hints.into_iter().map(|s| s.to_string()).collect()),
+        cache_ttl: None,
+        cache: RefCell::new(None),
+    }
+}
+
+impl<Provider> DynamicKeywordSetArgParser<Provider>
+where
+    Provider: KeywordProvider,
+{
+    /// Remembers keywords returned by the provider for `ttl`, so that the
+    /// provider is not queried on every keystroke.
+    pub fn with_cache_ttl(self, ttl: Duration) -> Self {
+        Self {
+            cache_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Drops cached keywords, if any, so that the next call queries the
+    /// provider.  Useful when the application knows that the set has changed.
+    pub fn invalidate(&self) {
+        *self.cache.borrow_mut() = None;
+    }
+
+    fn keywords(&self) -> Vec<String> {
+        let ttl = match self.cache_ttl {
+            Some(ttl) => ttl,
+            None => return self.provider.keywords(),
+        };
+
+        let now = Instant::now();
+        let mut cache = self.cache.borrow_mut();
+        match &*cache {
+            Some((fetched, keywords)) if now.duration_since(*fetched) < ttl => {
+                keywords.clone()
+            }
+            _ => {
+                let keywords = self.provider.keywords();
+                *cache = Some((now, keywords.clone()));
+                keywords
+            }
+        }
+    }
+
+    fn hints_for(&self, keywords: &[String]) -> Vec<String> {
+        match &self.hints {
+            Some(hints) => hints.clone(),
+            None if keywords.is_empty() => vec![NO_KEYWORDS_HINT.to_string()],
+            None => keywords.to_vec(),
+        }
+    }
+}
+
+impl<Provider> ContextFreeArgParser<String>
+    for DynamicKeywordSetArgParser<Provider>
+where
+    Provider: KeywordProvider,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<String> {
+        let keywords = self.keywords();
+
+        if keywords.is_empty() {
+            return ArgParseRes::Failed {
+                parsed_up_to: 0,
+                reason: self.hints_for(&keywords),
+            };
+        }
+
+        let hints = self.hints_for(&keywords);
+        keyword_set_with_hint(keywords, hints).parse(input)
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        let keywords = self.keywords();
+
+        if keywords.is_empty() {
+            return vec![];
+        }
+
+        keyword_set(keywords).suggestion(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        self.hints_for(&self.keywords())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dynamic_keyword_set, dynamic_keyword_set_with_hint};
+
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::ContextFreeArgParser;
+
+    fn vec_str(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn follows_provider() {
+        let sessions = Rc::new(RefCell::new(vec_str(&["probe0", "probe1"])));
+
+        let parser = {
+            let sessions = sessions.clone();
+            dynamic_keyword_set(move || sessions.borrow().clone())
+        };
+        let parser = Rc::new(parser);
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser.clone());
+
+        check_hint(&["probe0", "probe1"]);
+        check_parse("probe1", "probe1".to_string());
+        check_failure("probe2", 5, &["probe0", "probe1"]);
+        check_suggestions("p", &["probe0", "probe1"]);
+
+        sessions.borrow_mut().push("probe2".to_string());
+
+        check_hint(&["probe0", "probe1", "probe2"]);
+        check_parse("probe2", "probe2".to_string());
+        check_suggestions("probe", &["probe0", "probe1", "probe2"]);
+
+        sessions.borrow_mut().clear();
+
+        check_hint(&["<none available>"]);
+        check_failure("probe0", 0, &["<none available>"]);
+        check_suggestions("", &[]);
+    }
+
+    #[test]
+    fn with_hint() {
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                dynamic_keyword_set_with_hint(
+                    || vec_str(&["main", "boot"]),
+                    ["<target>"],
+                ),
+            );
+
+        check_hint(&["<target>"]);
+        check_parse("boot", "boot".to_string());
+        check_failure("b", 1, &["<target>"]);
+
+        let (check_hint, _, _, check_failure) = build_cf_parse_checkers(
+            "parser",
+            dynamic_keyword_set_with_hint(Vec::new, ["<target>"]),
+        );
+
+        check_hint(&["<target>"]);
+        check_failure("main", 0, &["<target>"]);
+    }
+
+    #[test]
+    fn caching() {
+        let calls = Rc::new(Cell::new(0));
+
+        let provider = {
+            let calls = calls.clone();
+            move || {
+                calls.set(calls.get() + 1);
+                vec_str(&["a", "b"])
+            }
+        };
+
+        let parser = dynamic_keyword_set(provider.clone());
+        parser.suggestion("");
+        parser.parse("a");
+        assert_eq!(calls.get(), 2);
+
+        calls.set(0);
+        let parser = dynamic_keyword_set(provider.clone())
+            .with_cache_ttl(Duration::from_secs(3600));
+        parser.suggestion("");
+        parser.parse("a");
+        parser.hint();
+        assert_eq!(calls.get(), 1);
+
+        parser.invalidate();
+        parser.parse("b");
+        assert_eq!(calls.get(), 2);
+
+        calls.set(0);
+        let parser = dynamic_keyword_set(provider)
+            .with_cache_ttl(Duration::from_millis(1));
+        parser.parse("a");
+        sleep(Duration::from_millis(5));
+        parser.parse("a");
+        assert_eq!(calls.get(), 2);
+    }
+}
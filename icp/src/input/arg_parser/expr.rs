@@ -0,0 +1,744 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for integer arithmetic expressions, such as `base+0x40*4` or
+//! `(end-start)/2`.
+//!
+//! Supported operators, from the lowest to the highest precedence, are `|`,
+//! `^`, `&`, `<< >>`, `+ -`, `* / %`, and unary `- ~ +`.  Precedence matches
+//! the one used in Rust.  Parentheses can be used for grouping.  Numbers are
+//! integer literals in any radix supported by [`super::radix_int`].
+//! Identifiers are resolved via a [`SymbolProvider`].
+//!
+//! As arguments are separated by whitespace, expressions can not contain any
+//! spaces.
+
+use num_traits::{NumCast, PrimInt};
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::radix_int::{
+    int_literal_prefix_len, parse_int_literal, radix_int_for_range,
+    radix_int_for_range_and_name, Radix, RadixIntArgParser,
+};
+use super::{ArgParseRes, ContextFreeArgParser};
+
+use crate::input::common_prefix::common_prefix_len;
+
+/// Source of values for identifiers used in expressions.
+pub trait SymbolProvider {
+    /// Value of the symbol called `name`, or `None` if there is no such
+    /// symbol.
+    fn value(&self, name: &str) -> Option<i128>;
+
+    /// All the known symbol names.  Used for suggestions and in the error
+    /// messages.
+    fn names(&self) -> Vec<String>;
+}
+
+/// A provider that does not know any symbols.  Used when only numbers are
+/// allowed in expressions.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct NoSymbols;
+
+impl SymbolProvider for NoSymbols {
+    fn value(&self, _name: &str) -> Option<i128> {
+        None
+    }
+
+    fn names(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+impl SymbolProvider for Vec<(String, i128)> {
+    fn value(&self, name: &str) -> Option<i128> {
+        self.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|(n, _)| n.clone()).collect()
+    }
+}
+
+impl SymbolProvider for HashMap<String, i128> {
+    fn value(&self, name: &str) -> Option<i128> {
+        self.get(name).copied()
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = self.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+}
+
+/// Parses an arithmetic expression and evaluates it into a value of type `T`.
+/// All the intermediate computations are done using `i128`, and any overflow
+/// is reported as an error.  The final value is then checked against the
+/// configured range.
+///
+/// Failures point at the token that could not be parsed or evaluated: for
+/// syntax errors `parsed_up_to` is the start of the offending token, for
+/// unknown symbols it is the end of the longest prefix shared with a known
+/// symbol, and for arithmetic errors it is the position of the operator that
+/// failed.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ExprArgParser<T, Symbols>
+where
+    T: PrimInt + FromStr + Display,
+    Symbols: SymbolProvider,
+{
+    bounds: RadixIntArgParser<T>,
+    symbols: Symbols,
+}
+
+/// The allowed range of values matches the range of values for the `T` type.
+/// Values in hints are shown using `radix`.
+pub fn expr<T>(radix: Radix) -> ExprArgParser<T, NoSymbols>
+where
+    T: PrimInt + FromStr + Display,
+{
+    expr_for_range(T::min_value(), T::max_value(), radix)
+}
+
+/// Restricts the allowed range of the result to be `[min, max]` - both end are
+/// included.
+pub fn expr_for_range<T>(
+    min: T,
+    max: T,
+    radix: Radix,
+) -> ExprArgParser<T, NoSymbols>
+where
+    T: PrimInt + FromStr + Display,
+{
+    ExprArgParser {
+        bounds: radix_int_for_range(min, max, radix),
+        symbols: NoSymbols,
+    }
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn expr_with_name<T, Name>(
+    radix: Radix,
+    name: Name,
+) -> ExprArgParser<T, NoSymbols>
+where
+    T: PrimInt + FromStr + Display,
+    Name: Into<String>,
+{
+    expr_for_range_and_name(T::min_value(), T::max_value(), radix, name)
+}
+
+/// Restricts the allowed range of the result to be `[min, max]` - both end are
+/// included.  Also names the argument.  Name will be included in the hint and
+/// in the error messages.
+pub fn expr_for_range_and_name<T, Name>(
+    min: T,
+    max: T,
+    radix: Radix,
+    name: Name,
+) -> ExprArgParser<T, NoSymbols>
+where
+    T: PrimInt + FromStr + Display,
+    Name: Into<String>,
+{
+    ExprArgParser {
+        bounds: radix_int_for_range_and_name(min, max, radix, name),
+        symbols: NoSymbols,
+    }
+}
+
+impl<T, Symbols> ExprArgParser<T, Symbols>
+where
+    T: PrimInt + FromStr + Display,
+    Symbols: SymbolProvider,
+{
+    /// Allows identifiers in expressions, resolving them via `symbols`.
+    pub fn with_symbols<NewSymbols>(
+        self,
+        symbols: NewSymbols,
+    ) -> ExprArgParser<T, NewSymbols>
+    where
+        NewSymbols: SymbolProvider,
+    {
+        ExprArgParser {
+            bounds: self.bounds,
+            symbols,
+        }
+    }
+
+    /// Computes the value of the `input` expression, without any range checks.
+    fn evaluate(&self, input: &str) -> Result<i128, ExprError> {
+        tokenize(input).and_then(|tokens| {
+            Evaluator {
+                tokens,
+                next: 0,
+                input_len: input.len(),
+                symbols: &self.symbols,
+            }
+            .evaluate()
+        })
+    }
+
+    /// Checks the final value against the configured range.  Values that do
+    /// not even fit into `T` are reported against the closest bound.
+    fn check_range(&self, v: i128, input: &str) -> ArgParseRes<T> {
+        if let Some(v) = <T as NumCast>::from(v) {
+            return self.bounds.check_range(v, input.len());
+        }
+
+        let (label, bound) = if v < 0 {
+            ("min", self.bounds.min)
+        } else {
+            ("max", self.bounds.max)
+        };
+        let bound = self.bounds.radix.format(bound);
+        ArgParseRes::Failed {
+            parsed_up_to: input.len(),
+            reason: match &self.bounds.name {
+                Some(name) => vec![format!("{} {}: {}", label, name, bound)],
+                None => vec![format!("{}: {}", label, bound)],
+            },
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Token<'input> {
+    Number(i128),
+    Ident(&'input str),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+/// Operators, with the longest ones first, so that `<<` is not split.
+const OPERATORS: &[&str] =
+    &["<<", ">>", "+", "-", "*", "/", "%", "&", "|", "^", "~"];
+
+/// Binary operators, grouped by precedence, from the lowest to the highest.
+const BINARY_LEVELS: &[&[&str]] = &[
+    &["|"],
+    &["^"],
+    &["&"],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+#[derive(PartialEq, Clone, Debug)]
+struct ExprError {
+    parsed_up_to: usize,
+    reason: String,
+    /// The token at fault, if any.
+    span: Option<Range<usize>>,
+}
+
+impl ExprError {
+    fn new<Reason>(parsed_up_to: usize, reason: Reason) -> Self
+    where
+        Reason: Into<String>,
+    {
+        Self {
+            parsed_up_to,
+            reason: reason.into(),
+            span: None,
+        }
+    }
+
+    fn with_span(self, span: Range<usize>) -> Self {
+        Self {
+            span: Some(span),
+            ..self
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Splits `input` into tokens, each paired with the position of its first
+/// character.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token<'_>)>, ExprError> {
+    let mut tokens = vec![];
+    let mut pos = 0;
+
+    while let Some(c) = input[pos..].chars().next() {
+        let rest = &input[pos..];
+
+        let (token, len) = if c.is_ascii_digit() {
+            let len = int_literal_prefix_len(rest);
+            let value =
+                parse_int_literal::<i128>(&rest[..len]).map_err(|_| {
+                    ExprError::new(pos + len, "number is too large")
+                        .with_span(pos..pos + len)
+                })?;
+            (Token::Number(value), len)
+        } else if is_ident_start(c) {
+            let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+            (Token::Ident(&rest[..len]), len)
+        } else if c == '(' {
+            (Token::Open, 1)
+        } else if c == ')' {
+            (Token::Close, 1)
+        } else {
+            match OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+                Some(op) => (Token::Op(op), op.len()),
+                None => {
+                    return Err(ExprError::new(
+                        pos,
+                        format!("unexpected character: '{}'", c),
+                    )
+                    .with_span(pos..pos + c.len_utf8()))
+                }
+            }
+        };
+
+        tokens.push((pos, token));
+        pos += len;
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser that computes the value of the expression as it
+/// goes.
+struct Evaluator<'input, 'symbols, Symbols>
+where
+    Symbols: SymbolProvider,
+{
+    tokens: Vec<(usize, Token<'input>)>,
+    next: usize,
+    input_len: usize,
+    symbols: &'symbols Symbols,
+}
+
+impl<'input, 'symbols, Symbols> Evaluator<'input, 'symbols, Symbols>
+where
+    Symbols: SymbolProvider,
+{
+    fn peek(&self) -> Option<(usize, Token<'input>)> {
+        self.tokens.get(self.next).copied()
+    }
+
+    /// Characters of the next token, or an empty range at the end of the
+    /// input.  Tokens are not separated by anything, so every token ends where
+    /// the following one starts.
+    fn next_span(&self) -> Range<usize> {
+        let start = |i: usize| {
+            self.tokens.get(i).map_or(self.input_len, |(pos, _)| *pos)
+        };
+        start(self.next)..start(self.next + 1)
+    }
+
+    fn evaluate(&mut self) -> Result<i128, ExprError> {
+        let v = self.binary(0)?;
+
+        match self.peek() {
+            None => Ok(v),
+            Some((pos, Token::Close)) => {
+                Err(ExprError::new(pos, "unmatched ')'")
+                    .with_span(self.next_span()))
+            }
+            Some((pos, _)) => Err(ExprError::new(pos, "expected an operator")
+                .with_span(self.next_span())),
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<i128, ExprError> {
+        let operators = match BINARY_LEVELS.get(level) {
+            Some(operators) => *operators,
+            None => return self.unary(),
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+        while let Some((pos, Token::Op(op))) = self.peek() {
+            if !operators.contains(&op) {
+                break;
+            }
+            let op_span = self.next_span();
+            self.next += 1;
+
+            let rhs = self.binary(level + 1)?;
+            lhs = apply_binary(op, lhs, rhs).map_err(|reason| {
+                ExprError::new(pos, reason).with_span(op_span.clone())
+            })?;
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<i128, ExprError> {
+        match self.peek() {
+            Some((pos, Token::Op(op))) if ["-", "~", "+"].contains(&op) => {
+                let op_span = self.next_span();
+                self.next += 1;
+                let v = self.unary()?;
+                match op {
+                    "-" => v.checked_neg().ok_or_else(|| {
+                        ExprError::new(pos, "overflow").with_span(op_span)
+                    }),
+                    "~" => Ok(!v),
+                    _ => Ok(v),
+                }
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<i128, ExprError> {
+        let (pos, token) = match self.peek() {
+            Some(next) => next,
+            None => {
+                return Err(ExprError::new(
+                    self.input_len,
+                    "expected a number, a symbol or '('",
+                ))
+            }
+        };
+        let span = self.next_span();
+        self.next += 1;
+
+        match token {
+            Token::Number(v) => Ok(v),
+            Token::Ident(name) => self.symbols.value(name).ok_or_else(|| {
+                let matched = self
+                    .symbols
+                    .names()
+                    .iter()
+                    .map(|known| common_prefix_len(name, known))
+                    .max()
+                    .unwrap_or(0);
+                ExprError::new(
+                    pos + matched,
+                    format!("unknown symbol: {}", name),
+                )
+                .with_span(span)
+            }),
+            Token::Open => {
+                let v = self.binary(0)?;
+                match self.peek() {
+                    Some((_, Token::Close)) => {
+                        self.next += 1;
+                        Ok(v)
+                    }
+                    None => Err(ExprError::new(self.input_len, "expected ')'")),
+                    Some((pos, _)) => Err(ExprError::new(pos, "expected ')'")
+                        .with_span(self.next_span())),
+                }
+            }
+            Token::Op(_) | Token::Close => {
+                Err(ExprError::new(pos, "expected a number, a symbol or '('")
+                    .with_span(span))
+            }
+        }
+    }
+}
+
+fn apply_binary(op: &str, lhs: i128, rhs: i128) -> Result<i128, &'static str> {
+    let shift = |rhs: i128| {
+        if (0..128).contains(&rhs) {
+            Ok(rhs as u32)
+        } else {
+            Err("shift amount must be within 0-127")
+        }
+    };
+
+    let res = match op {
+        "+" => lhs.checked_add(rhs),
+        "-" => lhs.checked_sub(rhs),
+        "*" => lhs.checked_mul(rhs),
+        "/" | "%" if rhs == 0 => return Err("division by zero"),
+        "/" => lhs.checked_div(rhs),
+        "%" => lhs.checked_rem(rhs),
+        "<<" => {
+            let shift = shift(rhs)?;
+            // Bits shifted out, including the sign bit, are an overflow.
+            Some(lhs << shift).filter(|res| res >> shift == lhs)
+        }
+        ">>" => Some(lhs >> shift(rhs)?),
+        "&" => Some(lhs & rhs),
+        "|" => Some(lhs | rhs),
+        "^" => Some(lhs ^ rhs),
+        _ => unreachable!("Unexpected binary operator: {}", op),
+    };
+
+    res.ok_or("overflow")
+}
+
+impl<T, Symbols> ContextFreeArgParser<T> for ExprArgParser<T, Symbols>
+where
+    T: PrimInt + FromStr + Display,
+    Symbols: SymbolProvider,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<T> {
+        match self.evaluate(input) {
+            Ok(v) => self.check_range(v, input),
+            Err(ExprError {
+                parsed_up_to,
+                reason,
+                ..
+            }) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: vec![reason],
+            },
+        }
+    }
+
+    /// The token that could not be parsed or evaluated.  Range check failures
+    /// and expressions that end too early are attributed to the whole
+    /// argument.
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.evaluate(input).err().and_then(|err| err.span)
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        // Only symbols are suggested, when the prefix ends with a symbol
+        // name, or with a place where an operand is expected.
+        let ident_start =
+            prefix.rfind(|c| !is_ident_char(c)).map_or(0, |pos| pos + 1);
+        let (before, ident) = prefix.split_at(ident_start);
+
+        let operand_expected = match before.chars().last() {
+            None => true,
+            Some(c) => c == '(' || OPERATORS.iter().any(|op| op.ends_with(c)),
+        };
+        if !operand_expected
+            || ident.chars().next().is_some_and(|c| !is_ident_start(c))
+        {
+            return vec![];
+        }
+
+        self.symbols
+            .names()
+            .into_iter()
+            .filter(|name| name.starts_with(ident) && name.len() > ident.len())
+            .map(|name| format!("{}{}", before, name))
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let min = self.bounds.radix.format(self.bounds.min);
+        let max = self.bounds.radix.format(self.bounds.max);
+        let separator = if self.bounds.min < T::zero() {
+            " - "
+        } else {
+            "-"
+        };
+
+        match &self.bounds.name {
+            Some(name) => vec![format!(
+                "<{} expression: {}{}{}>",
+                name, min, separator, max
+            )],
+            None => vec![format!("<expression: {}{}{}>", min, separator, max)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        expr, expr_for_range, expr_for_range_and_name, expr_with_name,
+    };
+
+    use std::collections::HashMap;
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{ArgParseRes, ContextFreeArgParser, Radix};
+
+    #[test]
+    fn arithmetic() {
+        let parser = expr::<i64>(Radix::Dec);
+        let expected_hint =
+            &["<expression: -9223372036854775808 - 9223372036854775807>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("42", 42);
+        check_parse("0x40*4", 256);
+        check_parse("1+2*3", 7);
+        check_parse("(1+2)*3", 9);
+        check_parse("10-4-3", 3);
+        check_parse("100/7%4", 2);
+        check_parse("-5+~0", -6);
+        check_parse("--5", 5);
+        check_parse("+3", 3);
+        check_parse("1<<4|1", 17);
+        check_parse("0xff&0b1010^1", 11);
+        check_parse("0x80>>3", 16);
+        check_parse("1_000*0o10", 8000);
+        check_parse("((2))", 2);
+
+        let expected_operand = &["expected a number, a symbol or '('"];
+        check_failure("", 0, expected_operand);
+        check_failure("1+", 2, expected_operand);
+        check_failure("1+*2", 2, expected_operand);
+        check_failure("()", 1, expected_operand);
+        check_failure("(1+2", 4, &["expected ')'"]);
+        check_failure("(1+2(", 4, &["expected ')'"]);
+        check_failure("1+2)", 3, &["unmatched ')'"]);
+        check_failure("1(2)", 1, &["expected an operator"]);
+        check_failure("12abc", 2, &["expected an operator"]);
+        check_failure("1+foo", 2, &["unknown symbol: foo"]);
+        check_failure("1 + 2", 1, &["unexpected character: ' '"]);
+        check_failure("2<3", 1, &["unexpected character: '<'"]);
+        check_failure("1/0", 1, &["division by zero"]);
+        check_failure("2+1%0", 3, &["division by zero"]);
+        check_failure("1<<128", 1, &["shift amount must be within 0-127"]);
+        check_failure("1<<127", 1, &["overflow"]);
+        check_failure(
+            "0x7fff_ffff_ffff_ffff+1",
+            23,
+            &["max: 9223372036854775807"],
+        );
+        check_failure(
+            "0xffff_ffff_ffff_ffff*0xffff_ffff_ffff_ffff",
+            21,
+            &["overflow"],
+        );
+        check_failure(
+            "1+0x1_0000_0000_0000_0000_0000_0000_0000_0000",
+            45,
+            &["number is too large"],
+        );
+
+        check_suggestions("", &[]);
+        check_suggestions("1+", &[]);
+    }
+
+    #[test]
+    fn failure_spans() {
+        let symbols = vec![("base".to_string(), 0x4000)];
+        let parser = expr::<u64>(Radix::Hex).with_symbols(symbols);
+
+        let span = |input: &str| match parser.parse(input) {
+            ArgParseRes::Failed { .. } => parser.failure_span(input),
+            ArgParseRes::Parsed(v) => panic!("'{}' parsed as {}", input, v),
+        };
+
+        assert_eq!(span("base+0x4g*4"), Some(8..9));
+        assert_eq!(span("base+bad"), Some(5..8));
+        assert_eq!(span("1+*2"), Some(2..3));
+        assert_eq!(span("1/0"), Some(1..2));
+        assert_eq!(span("(1)2"), Some(3..4));
+        assert_eq!(span("1+2)"), Some(3..4));
+        assert_eq!(span("(1+2(3"), Some(4..5));
+        assert_eq!(span("1?2"), Some(1..2));
+        assert_eq!(
+            span("1+0x1_0000_0000_0000_0000_0000_0000_0000_0000"),
+            Some(2..45)
+        );
+        // Nothing to point at, when the input ends too early.
+        assert_eq!(span("1+"), None);
+        assert_eq!(span("(1"), None);
+        // Range checks apply to the whole expression.
+        assert_eq!(span("0x1_0000_0000_0000_0000"), None);
+    }
+
+    #[test]
+    fn range_and_name() {
+        let parser =
+            expr_for_range_and_name(0x1000u32, 0x1fff, Radix::Hex, "addr");
+        let expected_hint = &["<addr expression: 0x1000-0x1fff>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0x1000+0x10*4", 0x1040);
+        check_failure("0x1000-1", 8, &["min addr: 0x1000"]);
+        check_failure("0x2000", 6, &["max addr: 0x1fff"]);
+        check_failure("-1", 2, &["min addr: 0x1000"]);
+
+        let (check_hint, _, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                expr_for_range(-10i8, 10, Radix::Dec),
+            );
+
+        check_hint(&["<expression: -10 - 10>"]);
+        check_parse("-2*5", -10);
+        check_failure("1000", 4, &["max: 10"]);
+
+        let (check_hint, _, _, _) = build_cf_parse_checkers(
+            "parser",
+            expr_with_name::<u8, _>(Radix::Bin, "mask"),
+        );
+
+        check_hint(&["<mask expression: 0b0-0b11111111>"]);
+    }
+
+    #[test]
+    fn symbols() {
+        let symbols = vec![
+            ("base".to_string(), 0x4000),
+            ("bar".to_string(), 3),
+            ("end".to_string(), 0x4100),
+            ("start".to_string(), 0x4000),
+        ];
+        let parser = expr::<u64>(Radix::Hex).with_symbols(symbols);
+
+        let (_check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("base+0x40*4", 0x4100);
+        check_parse("(end-start)/2", 0x80);
+        check_parse("bar", 3);
+
+        check_failure("bas", 3, &["unknown symbol: bas"]);
+        check_failure("1+bad", 4, &["unknown symbol: bad"]);
+        check_failure("stop", 2, &["unknown symbol: stop"]);
+        check_failure("base*(bar", 9, &["expected ')'"]);
+
+        check_suggestions("", &["base", "bar", "end", "start"]);
+        check_suggestions("b", &["base", "bar"]);
+        check_suggestions("bas", &["base"]);
+        check_suggestions("base", &[]);
+        check_suggestions(
+            "base+",
+            &["base+base", "base+bar", "base+end", "base+start"],
+        );
+        check_suggestions("(end-s", &["(end-start"]);
+        check_suggestions("0x", &[]);
+        check_suggestions("0xb", &[]);
+        check_suggestions("base+1", &[]);
+        check_suggestions("(end)", &[]);
+
+        let mut symbols = HashMap::new();
+        symbols.insert("two".to_string(), 2);
+        symbols.insert("three".to_string(), 3);
+        let parser = expr::<u8>(Radix::Dec).with_symbols(symbols);
+
+        let (_, check_suggestions, check_parse, _) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("two*three", 6);
+        check_suggestions("t", &["three", "two"]);
+    }
+}
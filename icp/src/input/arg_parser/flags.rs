@@ -0,0 +1,325 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for sets of bit flags, such as `READ|WRITE|0x4` or `READ,EXEC`.
+
+use num_traits::PrimInt;
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::string::ToString;
+
+use super::radix_int::parse_int_literal;
+use super::{ArgParseRes, ContextFreeArgParser};
+
+use crate::input::common_prefix::common_prefix_len;
+
+/// Characters that separate individual flags.  Both could be used in the same
+/// argument.
+const SEPARATORS: &[char] = &['|', ','];
+
+/// Parses a list of flag names, separated by `|` or `,`, and combines flag
+/// values into a single mask.  Each flag may be listed only once.
+///
+/// When enabled with [`FlagsArgParser::with_raw_values()`], integer literals
+/// are also accepted in place of flag names, and are combined into the mask
+/// as is.
+///
+/// Use [`FlagsArgParser::into_set()`] to get individual flag values, rather
+/// than the combined mask.
+#[derive(PartialEq, Clone, Debug)]
+pub struct FlagsArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    flags: Vec<(String, T)>,
+    raw_values: bool,
+}
+
+impl<T> FlagsArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn new(flags: Vec<(String, T)>) -> Self {
+        if flags.is_empty() {
+            panic!("`flags` should not be empty");
+        }
+
+        Self {
+            flags,
+            raw_values: false,
+        }
+    }
+
+    /// Allows integer literals, in any radix, in addition to the flag names.
+    pub fn with_raw_values(mut self) -> Self {
+        self.raw_values = true;
+        self
+    }
+
+    /// Converts this parser into one that returns individual flag values, in
+    /// the order they appear in the input, instead of the combined mask.
+    pub fn into_set(self) -> FlagSetArgParser<T> {
+        FlagSetArgParser { flags: self }
+    }
+}
+
+/// Accepts names from the `flags` table, which holds `(name, value)` pairs.
+pub fn flags<T, Name, Flags>(flags: Flags) -> FlagsArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: ToString,
+    Flags: IntoIterator<Item = (Name, T)>,
+{
+    FlagsArgParser::new(
+        flags
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    )
+}
+
+impl<T> FlagsArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    /// Parses `input` into a list of flag values.
+    fn parse_values<Res>(
+        &self,
+        input: &str,
+    ) -> Result<Vec<T>, ArgParseRes<Res>> {
+        let mut values = vec![];
+        let mut seen = vec![];
+        let mut offset = 0;
+
+        for item in input.split(SEPARATORS) {
+            if seen.contains(&item) {
+                return Err(ArgParseRes::Failed {
+                    parsed_up_to: offset,
+                    reason: vec![format!("duplicate flag: {}", item)],
+                });
+            }
+
+            values.push(self.parse_item(item, offset)?);
+            seen.push(item);
+            offset += item.len() + 1;
+        }
+
+        Ok(values)
+    }
+
+    /// Parses a single flag name, or a raw value, that starts at `offset` in
+    /// the whole argument.
+    fn parse_item<Res>(
+        &self,
+        item: &str,
+        offset: usize,
+    ) -> Result<T, ArgParseRes<Res>> {
+        if let Some((_, value)) =
+            self.flags.iter().find(|(name, _)| name == item)
+        {
+            return Ok(*value);
+        }
+
+        let starts_with_digit =
+            item.chars().next().is_some_and(|c| c.is_ascii_digit());
+        if self.raw_values && starts_with_digit {
+            return parse_int_literal(item).map_err(|parsed_up_to| {
+                ArgParseRes::Failed {
+                    parsed_up_to: offset + parsed_up_to,
+                    reason: self.names_hint(),
+                }
+            });
+        }
+
+        let longest_match = self
+            .flags
+            .iter()
+            .map(|(name, _)| common_prefix_len(item, name))
+            .max()
+            .unwrap_or(0);
+
+        Err(ArgParseRes::Failed {
+            parsed_up_to: offset + longest_match,
+            reason: self.names_hint(),
+        })
+    }
+
+    fn name_suggestions(&self, prefix: &str) -> Vec<String> {
+        let (before, last) = match prefix.rfind(SEPARATORS) {
+            Some(i) => prefix.split_at(i + 1),
+            None => ("", prefix),
+        };
+        let used = before.split(SEPARATORS).collect::<Vec<_>>();
+
+        self.flags
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|name| {
+                name.starts_with(last)
+                    && name.len() > last.len()
+                    && !used.contains(&name.as_str())
+            })
+            .map(|name| format!("{}{}", before, name))
+            .collect()
+    }
+
+    fn names_hint(&self) -> Vec<String> {
+        let mut hint = self
+            .flags
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if self.raw_values {
+            hint.push("<number>".to_string());
+        }
+        hint
+    }
+}
+
+impl<T> ContextFreeArgParser<T> for FlagsArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<T> {
+        match self.parse_values(input) {
+            Ok(values) => ArgParseRes::Parsed(
+                values.into_iter().fold(T::zero(), |mask, v| mask | v),
+            ),
+            Err(failure) => failure,
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.name_suggestions(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        self.names_hint()
+    }
+}
+
+/// Same as [`FlagsArgParser`], but returns individual flag values, rather than
+/// the combined mask.  Constructed via [`FlagsArgParser::into_set()`].
+#[derive(PartialEq, Clone, Debug)]
+pub struct FlagSetArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    flags: FlagsArgParser<T>,
+}
+
+impl<T> ContextFreeArgParser<Vec<T>> for FlagSetArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<Vec<T>> {
+        match self.flags.parse_values(input) {
+            Ok(values) => ArgParseRes::Parsed(values),
+            Err(failure) => failure,
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.flags.name_suggestions(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        self.flags.names_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::flags;
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn mask() {
+        let parser =
+            flags(vec![("READ", 0x1u32), ("WRITE", 0x2), ("EXEC", 0x4)]);
+        let expected_hint = &["READ", "WRITE", "EXEC"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("READ", 0x1);
+        check_parse("READ|WRITE", 0x3);
+        check_parse("EXEC,READ", 0x5);
+        check_parse("WRITE|EXEC,READ", 0x7);
+
+        check_failure("", 0, expected_hint);
+        check_failure("READ|", 5, expected_hint);
+        check_failure("READ||WRITE", 5, expected_hint);
+        check_failure("RE", 2, expected_hint);
+        check_failure("READ|WRTE", 7, expected_hint);
+        check_failure("READ|0x4", 5, expected_hint);
+        check_failure("READ|WRITE|READ", 11, &["duplicate flag: READ"]);
+        check_failure("read", 0, expected_hint);
+
+        check_suggestions("", &["READ", "WRITE", "EXEC"]);
+        check_suggestions("R", &["READ"]);
+        check_suggestions("READ", &[]);
+        check_suggestions("READ|", &["READ|WRITE", "READ|EXEC"]);
+        check_suggestions("READ,E", &["READ,EXEC"]);
+        check_suggestions("READ|WRITE|", &["READ|WRITE|EXEC"]);
+        check_suggestions("READ|WRITE|EXEC|", &[]);
+        check_suggestions("X", &[]);
+    }
+
+    #[test]
+    fn raw_values() {
+        let parser = flags(vec![("A", 0x1u8), ("B", 0x2)]).with_raw_values();
+        let expected_hint = &["A", "B", "<number>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("A|0x4", 0x5);
+        check_parse("0b1000,B", 0xa);
+        check_parse("16", 0x10);
+
+        check_failure("A|0x", 3, expected_hint);
+        check_failure("A|4x", 3, expected_hint);
+        check_failure("A|256", 5, expected_hint);
+        check_failure("4|A|4", 4, &["duplicate flag: 4"]);
+    }
+
+    #[test]
+    fn set() {
+        let parser =
+            flags(vec![("READ", 0x1u32), ("WRITE", 0x2), ("EXEC", 0x4)])
+                .with_raw_values()
+                .into_set();
+        let expected_hint = &["READ", "WRITE", "EXEC", "<number>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("EXEC|READ", vec![0x4, 0x1]);
+        check_parse("WRITE,0x10", vec![0x2, 0x10]);
+
+        check_failure("EXEC|EXEC", 5, &["duplicate flag: EXEC"]);
+        check_failure("EXEC|X", 5, expected_hint);
+
+        check_suggestions("EXEC|", &["EXEC|READ", "EXEC|WRITE"]);
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use lazy_static::lazy_static;
+use num_traits::Float;
+use regex::Regex;
+
+use std::fmt::Display;
+use std::ops::Bound;
+use std::str::FromStr;
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// Returns the length of the longest prefix of `input` that forms a floating
+/// point number in decimal or scientific notation, or `0` if `input` does not
+/// start with a number.
+pub(super) fn float_prefix_len(input: &str) -> usize {
+    lazy_static! {
+        static ref NUMBER_PREFIX: Regex =
+            Regex::new(r"^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][+-]?[0-9]+)?")
+                .unwrap();
+    }
+
+    NUMBER_PREFIX.find(input).map_or(0, |m| m.end())
+}
+
+/// Parses floating point numbers in decimal or scientific notation.  NaN and
+/// infinities are never accepted.  See [`float_for_range_and_name()`] for
+/// details.
+#[derive(PartialEq, Clone, Debug)]
+pub struct FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+{
+    min: Bound<T>,
+    max: Bound<T>,
+    max_fraction_digits: Option<usize>,
+    name: Option<String>,
+}
+
+/// Accepts any finite value of type `T`.
+pub fn float<T>() -> FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+{
+    float_for_range(Bound::Unbounded, Bound::Unbounded)
+}
+
+/// Restricts the allowed range.  Either end may be inclusive, exclusive or
+/// unbounded.
+pub fn float_for_range<T>(min: Bound<T>, max: Bound<T>) -> FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+{
+    FloatArgParser {
+        min,
+        max,
+        max_fraction_digits: None,
+        name: None,
+    }
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn float_with_name<T, Name>(name: Name) -> FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+    Name: Into<String>,
+{
+    float_for_range_and_name(Bound::Unbounded, Bound::Unbounded, name)
+}
+
+/// Restricts the allowed range, similar to [`float_for_range()`].  Also names
+/// the argument.  Name will be included in the hint and in the error messages.
+pub fn float_for_range_and_name<T, Name>(
+    min: Bound<T>,
+    max: Bound<T>,
+    name: Name,
+) -> FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+    Name: Into<String>,
+{
+    FloatArgParser {
+        min,
+        max,
+        max_fraction_digits: None,
+        name: Some(name.into()),
+    }
+}
+
+impl<T> FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+{
+    /// Limits the precision of the accepted values to `digits` digits after
+    /// the decimal point.  Exponent is taken into account, so with `digits`
+    /// set to `1`, `1.25e1` is accepted, while `1.25` is not.
+    pub fn with_max_fraction_digits(mut self, digits: usize) -> Self {
+        self.max_fraction_digits = Some(digits);
+        self
+    }
+
+    fn named(&self, what: &str) -> String {
+        match &self.name {
+            Some(name) => format!("{} {}", what, name),
+            None => what.to_string(),
+        }
+    }
+
+    fn check_precision(&self, input: &str) -> Option<ArgParseRes<T>> {
+        let max_digits = self.max_fraction_digits?;
+
+        let (mantissa, exponent) = match input.find(['e', 'E']) {
+            Some(i) => (&input[..i], input[i + 1..].parse::<i64>().ok()?),
+            None => (input, 0),
+        };
+        // A mantissa without a dot has no fraction digits, but the exponent
+        // may still move the decimal point to the left.
+        let dot = mantissa.find('.');
+        let fraction_digits = dot.map_or(0, |dot| mantissa.len() - dot - 1);
+
+        // Exponents close to `i64::MIN` describe values too small to be
+        // represented.
+        let effective_digits =
+            match (fraction_digits as i64).checked_sub(exponent) {
+                Some(digits) => digits,
+                None => {
+                    return Some(ArgParseRes::Failed {
+                        parsed_up_to: input.len(),
+                        reason: vec![match &self.name {
+                            Some(name) => format!("{} is out of range", name),
+                            None => "value is out of range".to_string(),
+                        }],
+                    })
+                }
+            };
+        if effective_digits <= max_digits as i64 {
+            return None;
+        }
+
+        // Without an exponent we can point at the first extra digit.
+        // Otherwise the whole argument is at fault.
+        let parsed_up_to = match dot {
+            Some(dot) if exponent == 0 && mantissa.len() == input.len() => {
+                dot + 1 + max_digits
+            }
+            _ => input.len(),
+        };
+
+        Some(ArgParseRes::Failed {
+            parsed_up_to,
+            reason: vec![match &self.name {
+                Some(name) => format!(
+                    "max {} precision: {} digits after the decimal point",
+                    name, max_digits
+                ),
+                None => format!(
+                    "max precision: {} digits after the decimal point",
+                    max_digits
+                ),
+            }],
+        })
+    }
+
+    fn check_range(&self, v: T, input: &str) -> ArgParseRes<T> {
+        let failure = |what: &str, bound: T| ArgParseRes::Failed {
+            parsed_up_to: input.len(),
+            reason: vec![format!("{}: {}", self.named(what), bound)],
+        };
+
+        match self.min {
+            Bound::Included(min) if v < min => return failure("min", min),
+            Bound::Excluded(min) if v <= min => {
+                return failure("exclusive min", min)
+            }
+            _ => (),
+        }
+
+        match self.max {
+            Bound::Included(max) if v > max => return failure("max", max),
+            Bound::Excluded(max) if v >= max => {
+                return failure("exclusive max", max)
+            }
+            _ => (),
+        }
+
+        ArgParseRes::Parsed(v)
+    }
+}
+
+impl<T> ContextFreeArgParser<T> for FloatArgParser<T>
+where
+    T: Float + FromStr + Display,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<T> {
+        let parsed_up_to = float_prefix_len(input);
+        if parsed_up_to != input.len() || parsed_up_to == 0 {
+            return ArgParseRes::Failed {
+                parsed_up_to,
+                reason: self.hint(),
+            };
+        }
+
+        if let Some(failure) = self.check_precision(input) {
+            return failure;
+        }
+
+        match T::from_str(input) {
+            // Values that overflow parse as infinities, and we do not accept
+            // those.
+            Ok(v) if v.is_finite() => self.check_range(v, input),
+            Ok(_) | Err(_) => ArgParseRes::Failed {
+                parsed_up_to: input.len(),
+                reason: self.hint(),
+            },
+        }
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let range = match (&self.min, &self.max) {
+            (Bound::Unbounded, Bound::Unbounded) => None,
+            (min, max) => {
+                let min = match min {
+                    Bound::Included(min) => format!("[{}", min),
+                    Bound::Excluded(min) => format!("({}", min),
+                    Bound::Unbounded => "(-inf".to_string(),
+                };
+                let max = match max {
+                    Bound::Included(max) => format!("{}]", max),
+                    Bound::Excluded(max) => format!("{})", max),
+                    Bound::Unbounded => "inf)".to_string(),
+                };
+                Some(format!("{}, {}", min, max))
+            }
+        };
+
+        match (&self.name, range) {
+            (Some(name), Some(range)) => vec![format!("<{}: {}>", name, range)],
+            (Some(name), None) => vec![format!("<{}>", name)],
+            (None, Some(range)) => vec![format!("<{}>", range)],
+            (None, None) => vec!["<number>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    use super::{
+        float, float_for_range, float_for_range_and_name, float_with_name,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn f64_parsing() {
+        let parser = float::<f64>();
+        let expected_hint = &["<number>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0", 0.0);
+        check_parse("-1.5", -1.5);
+        check_parse("+2", 2.0);
+        check_parse(".25", 0.25);
+        check_parse("3.", 3.0);
+        check_parse("1e3", 1000.0);
+        check_parse("2.5E-1", 0.25);
+
+        check_failure("", 0, expected_hint);
+        check_failure("-", 0, expected_hint);
+        check_failure(".", 0, expected_hint);
+        check_failure("NaN", 0, expected_hint);
+        check_failure("inf", 0, expected_hint);
+        check_failure("-infinity", 0, expected_hint);
+        check_failure("1.5x", 3, expected_hint);
+        check_failure("1e", 1, expected_hint);
+        check_failure("1e+", 1, expected_hint);
+        check_failure("1.2.3", 3, expected_hint);
+        check_failure("1e400", 5, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("1", &[]);
+    }
+
+    #[test]
+    fn f32_with_name() {
+        let parser = float_with_name::<f32, _>("gain");
+        let expected_hint = &["<gain>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0.5", 0.5);
+        check_parse("-3e2", -300.0);
+
+        check_failure("1e39", 4, expected_hint);
+        check_failure("x", 0, expected_hint);
+    }
+
+    #[test]
+    fn inclusive_range() {
+        let parser = float_for_range(Included(-1.0f64), Included(1.0));
+        let expected_hint = &["<[-1, 1]>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("-1", -1.0);
+        check_parse("1.0", 1.0);
+        check_parse("0.5e-3", 0.0005);
+
+        check_failure("-1.01", 5, &["min: -1"]);
+        check_failure("1e1", 3, &["max: 1"]);
+        check_failure("z", 0, expected_hint);
+    }
+
+    #[test]
+    fn exclusive_range_and_name() {
+        let parser =
+            float_for_range_and_name(Excluded(0.0f64), Unbounded, "freq");
+        let expected_hint = &["<freq: (0, inf)>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("1e-9", 1e-9);
+        check_parse("1e300", 1e300);
+
+        check_failure("0", 1, &["exclusive min freq: 0"]);
+        check_failure("-0.0", 4, &["exclusive min freq: 0"]);
+        check_failure("-1", 2, &["exclusive min freq: 0"]);
+
+        let parser =
+            float_for_range_and_name(Unbounded, Excluded(100.0f64), "percent");
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["<percent: (-inf, 100)>"]);
+
+        check_parse("99.99", 99.99);
+        check_failure("100", 3, &["exclusive max percent: 100"]);
+    }
+
+    #[test]
+    fn precision() {
+        let parser =
+            float_for_range_and_name(Included(0.0f64), Included(5.0), "volts")
+                .with_max_fraction_digits(2);
+        let expected_precision =
+            &["max volts precision: 2 digits after the decimal point"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["<volts: [0, 5]>"]);
+
+        check_parse("3.3", 3.3);
+        check_parse("1.25", 1.25);
+        check_parse("0.125e1", 1.25);
+        check_parse("125e-2", 1.25);
+        check_parse("3", 3.0);
+
+        check_failure("1.255", 4, expected_precision);
+        check_failure("0.12345", 4, expected_precision);
+        check_failure("1.25e-1", 7, expected_precision);
+        check_failure("1e-3", 4, expected_precision);
+        check_failure("1255e-3", 7, expected_precision);
+        check_failure("1e-9223372036854775808", 22, &["volts is out of range"]);
+        check_failure("1e-9223372036854775807", 22, expected_precision);
+        check_failure("5.01", 4, &["max volts: 5"]);
+    }
+}
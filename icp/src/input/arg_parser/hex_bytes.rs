@@ -0,0 +1,376 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for raw bytes written in hex, such as `deadbeef`, `de:ad:be:ef` or
+//! `0xdeadbeef`.
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// Parses a string of hex digits into bytes.  Digits may be written as one
+/// continuous string, optionally prefixed with `0x`, or as pairs separated by
+/// colons.  Both upper and lower case digits are accepted.
+///
+/// The number of bytes could be restricted, see
+/// [`hex_bytes_for_len_and_name()`].
+#[derive(PartialEq, Clone, Debug)]
+pub struct HexBytesArgParser {
+    min_len: usize,
+    max_len: Option<usize>,
+    name: Option<String>,
+}
+
+/// Accepts any number of bytes.
+pub fn hex_bytes() -> HexBytesArgParser {
+    HexBytesArgParser {
+        min_len: 0,
+        max_len: None,
+        name: None,
+    }
+}
+
+/// Restricts the number of bytes to be within `[min_len, max_len]` - both end
+/// are included.  `None` for `max_len` means there is no upper limit.  Use
+/// the same value for `min_len` and `max_len` to require an exact length.
+pub fn hex_bytes_for_len(
+    min_len: usize,
+    max_len: Option<usize>,
+) -> HexBytesArgParser {
+    HexBytesArgParser {
+        min_len,
+        max_len,
+        name: None,
+    }
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn hex_bytes_with_name<Name>(name: Name) -> HexBytesArgParser
+where
+    Name: Into<String>,
+{
+    hex_bytes_for_len_and_name(0, None, name)
+}
+
+/// Restricts the number of bytes, similar to [`hex_bytes_for_len()`].  Also
+/// names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn hex_bytes_for_len_and_name<Name>(
+    min_len: usize,
+    max_len: Option<usize>,
+    name: Name,
+) -> HexBytesArgParser
+where
+    Name: Into<String>,
+{
+    HexBytesArgParser {
+        min_len,
+        max_len,
+        name: Some(name.into()),
+    }
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Matches `input` against a fixed `template`, where every `x` stands for a
+/// hex digit, and any other character must be present as is.  Hex digits are
+/// combined into bytes, in pairs, so `template` must contain an even number of
+/// `x`s.  On failure returns the position of the first character that does
+/// not match, or `input.len()` if `input` is too short.
+pub(super) fn parse_hex_template(
+    input: &str,
+    template: &str,
+) -> Result<Vec<u8>, usize> {
+    let mut digits = vec![];
+
+    for (i, c) in input.bytes().enumerate() {
+        match template.as_bytes().get(i) {
+            Some(b'x') => match hex_digit(c) {
+                Some(digit) => digits.push(digit),
+                None => return Err(i),
+            },
+            Some(expected) if *expected == c => (),
+            Some(_) | None => return Err(i),
+        }
+    }
+
+    if input.len() < template.len() {
+        return Err(input.len());
+    }
+
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+impl HexBytesArgParser {
+    fn failure(
+        &self,
+        parsed_up_to: usize,
+        reason: String,
+    ) -> ArgParseRes<Vec<u8>> {
+        ArgParseRes::Failed {
+            parsed_up_to,
+            reason: vec![reason],
+        }
+    }
+
+    fn hint_failure(&self, parsed_up_to: usize) -> ArgParseRes<Vec<u8>> {
+        ArgParseRes::Failed {
+            parsed_up_to,
+            reason: self.hint(),
+        }
+    }
+
+    fn named(&self, what: &str) -> String {
+        match &self.name {
+            Some(name) => format!("{} {}", what, name),
+            None => what.to_string(),
+        }
+    }
+
+    /// Parses a continuous string of hex digits that starts at `offset` in
+    /// the whole argument.
+    fn parse_continuous(
+        &self,
+        digits: &str,
+        offset: usize,
+    ) -> Result<Vec<u8>, ArgParseRes<Vec<u8>>> {
+        let digits = digits.as_bytes();
+
+        if let Some(i) = digits.iter().position(|c| hex_digit(*c).is_none()) {
+            return Err(self.hint_failure(offset + i));
+        }
+
+        if !digits.len().is_multiple_of(2) {
+            // The last digit is the one that is missing a pair.
+            return Err(self.failure(
+                offset + digits.len() - 1,
+                "odd number of hex digits".to_string(),
+            ));
+        }
+
+        Ok(digits
+            .chunks(2)
+            .map(|pair| {
+                hex_digit(pair[0]).unwrap() << 4 | hex_digit(pair[1]).unwrap()
+            })
+            .collect())
+    }
+
+    /// Parses hex digit pairs separated by colons.
+    fn parse_separated(
+        &self,
+        input: &str,
+    ) -> Result<Vec<u8>, ArgParseRes<Vec<u8>>> {
+        let mut bytes = vec![];
+        let mut offset = 0;
+
+        for pair in input.split(':') {
+            if let Some(i) = pair.bytes().position(|c| hex_digit(c).is_none()) {
+                return Err(self.hint_failure(offset + i));
+            }
+
+            match pair.len() {
+                2 => bytes.extend(self.parse_continuous(pair, offset)?),
+                1 => {
+                    return Err(self.failure(
+                        offset,
+                        "odd number of hex digits".to_string(),
+                    ))
+                }
+                0 => return Err(self.hint_failure(offset)),
+                _ => {
+                    return Err(self.failure(
+                        offset + 2,
+                        "expected ':' after every two hex digits".to_string(),
+                    ))
+                }
+            }
+
+            offset += pair.len() + 1;
+        }
+
+        Ok(bytes)
+    }
+
+    fn check_len(&self, bytes: Vec<u8>, input: &str) -> ArgParseRes<Vec<u8>> {
+        let bytes_str = |len: usize| {
+            if len == 1 {
+                "1 byte".to_string()
+            } else {
+                format!("{} bytes", len)
+            }
+        };
+
+        if Some(self.min_len) == self.max_len && bytes.len() != self.min_len {
+            return self.failure(
+                input.len(),
+                format!(
+                    "{}: {}",
+                    self.named("length"),
+                    bytes_str(self.min_len)
+                ),
+            );
+        }
+
+        if bytes.len() < self.min_len {
+            return self.failure(
+                input.len(),
+                format!(
+                    "{}: {}",
+                    self.named("min length"),
+                    bytes_str(self.min_len)
+                ),
+            );
+        }
+
+        match self.max_len {
+            Some(max_len) if bytes.len() > max_len => self.failure(
+                input.len(),
+                format!("{}: {}", self.named("max length"), bytes_str(max_len)),
+            ),
+            _ => ArgParseRes::Parsed(bytes),
+        }
+    }
+}
+
+impl ContextFreeArgParser<Vec<u8>> for HexBytesArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<Vec<u8>> {
+        let res = match input.get(0..2) {
+            Some("0x") | Some("0X") => self.parse_continuous(&input[2..], 2),
+            _ if input.contains(':') => self.parse_separated(input),
+            _ => self.parse_continuous(input, 0),
+        };
+
+        match res {
+            Ok(bytes) => self.check_len(bytes, input),
+            Err(failure) => failure,
+        }
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let len = match (self.min_len, self.max_len) {
+            (0, None) => None,
+            (min, None) => Some(format!(">={} bytes", min)),
+            (min, Some(max)) if min == max => Some(if min == 1 {
+                "1 byte".to_string()
+            } else {
+                format!("{} bytes", min)
+            }),
+            (min, Some(max)) => Some(format!("{}-{} bytes", min, max)),
+        };
+
+        match (&self.name, len) {
+            (Some(name), Some(len)) => vec![format!("<{}: {}>", name, len)],
+            (Some(name), None) => vec![format!("<{}>", name)],
+            (None, Some(len)) => vec![format!("<hex: {}>", len)],
+            (None, None) => vec!["<hex>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        hex_bytes, hex_bytes_for_len, hex_bytes_for_len_and_name,
+        hex_bytes_with_name,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn any_length() {
+        let parser = hex_bytes();
+        let expected_hint = &["<hex>"];
+        let odd = &["odd number of hex digits"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("deadbeef", vec![0xde, 0xad, 0xbe, 0xef]);
+        check_parse("DEADbeef", vec![0xde, 0xad, 0xbe, 0xef]);
+        check_parse("de:ad:be:ef", vec![0xde, 0xad, 0xbe, 0xef]);
+        check_parse("0xdeadbeef", vec![0xde, 0xad, 0xbe, 0xef]);
+        check_parse("0X00ff", vec![0x00, 0xff]);
+        check_parse("00", vec![0x00]);
+        check_parse("", vec![]);
+        check_parse("0x", vec![]);
+
+        check_failure("dea", 2, odd);
+        check_failure("0xdeadb", 6, odd);
+        check_failure("de:a:be", 3, odd);
+        check_failure("de:ad:b", 6, odd);
+        check_failure("dexdbeef", 2, expected_hint);
+        check_failure("0xdeadbeeg", 9, expected_hint);
+        check_failure("de:ag:be", 4, expected_hint);
+        check_failure("de::be", 3, expected_hint);
+        check_failure("de:ad:", 6, expected_hint);
+        check_failure(":de", 0, expected_hint);
+        check_failure(
+            "de:adbe",
+            5,
+            &["expected ':' after every two hex digits"],
+        );
+        check_failure("0xde:ad", 4, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("de", &[]);
+    }
+
+    #[test]
+    fn length_limits() {
+        let parser = hex_bytes_for_len_and_name(2, Some(4), "payload");
+        let expected_hint = &["<payload: 2-4 bytes>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0102", vec![1, 2]);
+        check_parse("01:02:03:04", vec![1, 2, 3, 4]);
+
+        check_failure("01", 2, &["min length payload: 2 bytes"]);
+        check_failure("0x0102030405", 12, &["max length payload: 4 bytes"]);
+        check_failure("010", 2, &["odd number of hex digits"]);
+
+        let (check_hint, _, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", hex_bytes_for_len(4, Some(4)));
+
+        check_hint(&["<hex: 4 bytes>"]);
+        check_parse("c0ffee00", vec![0xc0, 0xff, 0xee, 0x00]);
+        check_failure("c0ffee", 6, &["length: 4 bytes"]);
+        check_failure("c0ffee0000", 10, &["length: 4 bytes"]);
+
+        let (check_hint, _, _, check_failure) =
+            build_cf_parse_checkers("parser", hex_bytes_for_len(1, None));
+
+        check_hint(&["<hex: >=1 bytes>"]);
+        check_failure("0x", 2, &["min length: 1 byte"]);
+
+        let (check_hint, _, _, _) =
+            build_cf_parse_checkers("parser", hex_bytes_with_name("key"));
+
+        check_hint(&["<key>"]);
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for integer ranges, written as `start..end`, `start..=end` or
+//! `start+len`.
+
+use num_traits::PrimInt;
+
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use super::radix_int::{
+    int_literal_prefix_len, parse_int_literal, radix_int_for_range,
+    radix_int_for_range_and_name, Radix, RadixIntArgParser,
+};
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// Operators separating the start of the range from the rest.  Longest first,
+/// so that `..=` is not mistaken for `..`.
+const OPERATORS: &[&str] = &["..=", "..", "+"];
+
+/// Parses a non-empty range of integers, where both ends are within an outer
+/// limit.  Numbers are accepted in any radix, see [`super::radix_int`].
+///
+/// All three forms are converted into a `RangeInclusive`: `a..b` produces
+/// `a..=(b - 1)`, and `a+len` produces `a..=(a + len - 1)`.  As empty ranges
+/// are rejected, no information is lost in the conversion.  Unlike
+/// `std::ops::Range`, this can also represent a range that ends at
+/// `T::max_value()`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    /// Holds the outer limit, radix and name, and checks range ends against
+    /// the limit.
+    bounds: RadixIntArgParser<T>,
+}
+
+/// Both ends of the range are limited by the range of values for the `T`
+/// type.  Values in hints are shown using `radix`.
+pub fn int_range<T>(radix: Radix) -> IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    int_range_within(T::min_value(), T::max_value(), radix)
+}
+
+/// Both ends of the range must be within `[min, max]`.
+pub fn int_range_within<T>(min: T, max: T, radix: Radix) -> IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    IntRangeArgParser {
+        bounds: radix_int_for_range(min, max, radix),
+    }
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn int_range_with_name<T, Name>(
+    radix: Radix,
+    name: Name,
+) -> IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: Into<String>,
+{
+    int_range_within_and_name(T::min_value(), T::max_value(), radix, name)
+}
+
+/// Both ends of the range must be within `[min, max]`.  Also names the
+/// argument.  Name will be included in the hint and in the error messages.
+pub fn int_range_within_and_name<T, Name>(
+    min: T,
+    max: T,
+    radix: Radix,
+    name: Name,
+) -> IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: Into<String>,
+{
+    IntRangeArgParser {
+        bounds: radix_int_for_range_and_name(min, max, radix, name),
+    }
+}
+
+impl<T> IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn failure(
+        &self,
+        parsed_up_to: usize,
+        reason: &str,
+    ) -> ArgParseRes<RangeInclusive<T>> {
+        ArgParseRes::Failed {
+            parsed_up_to,
+            reason: vec![reason.to_string()],
+        }
+    }
+
+    fn hint_failure(
+        &self,
+        parsed_up_to: usize,
+    ) -> ArgParseRes<RangeInclusive<T>> {
+        ArgParseRes::Failed {
+            parsed_up_to,
+            reason: self.hint(),
+        }
+    }
+
+    /// Parses an integer literal that starts at `offset` in the whole
+    /// argument.
+    fn parse_number(
+        &self,
+        literal: &str,
+        offset: usize,
+    ) -> Result<T, ArgParseRes<RangeInclusive<T>>> {
+        parse_int_literal(literal)
+            .map_err(|parsed_up_to| self.hint_failure(offset + parsed_up_to))
+    }
+
+    /// Checks `v` against the outer limit.
+    fn check_bounds(
+        &self,
+        v: T,
+        parsed_up_to: usize,
+    ) -> Result<T, ArgParseRes<RangeInclusive<T>>> {
+        match self.bounds.check_range(v, parsed_up_to) {
+            ArgParseRes::Parsed(v) => Ok(v),
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            } => Err(ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            }),
+        }
+    }
+
+    fn parse_range(
+        &self,
+        input: &str,
+    ) -> Result<RangeInclusive<T>, ArgParseRes<RangeInclusive<T>>> {
+        let start_len = int_literal_prefix_len(input);
+        if start_len == 0 {
+            return Err(self.hint_failure(0));
+        }
+
+        let rest = &input[start_len..];
+        let operator = match OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            Some(operator) => *operator,
+            None => {
+                // A partial operator, like `.`, is still a valid prefix.
+                let partial = !rest.is_empty()
+                    && OPERATORS.iter().any(|op| op.starts_with(rest));
+                let parsed_up_to =
+                    start_len + if partial { rest.len() } else { 0 };
+                return Err(self.hint_failure(parsed_up_to));
+            }
+        };
+
+        let start = self.parse_number(&input[..start_len], 0)?;
+        let start = self.check_bounds(start, start_len)?;
+
+        let end_offset = start_len + operator.len();
+        let end = self.parse_number(&input[end_offset..], end_offset)?;
+
+        let end = match operator {
+            ".." => {
+                if end <= start {
+                    return Err(self.failure(
+                        input.len(),
+                        "end must be greater than start",
+                    ));
+                }
+                end - T::one()
+            }
+            "..=" => {
+                if end < start {
+                    return Err(self.failure(
+                        input.len(),
+                        "end must not be less than start",
+                    ));
+                }
+                end
+            }
+            _ => {
+                if end < T::one() {
+                    return Err(
+                        self.failure(input.len(), "length must be positive")
+                    );
+                }
+                match start.checked_add(&(end - T::one())) {
+                    Some(end) => end,
+                    None => {
+                        return Err(
+                            self.failure(input.len(), "range is too long")
+                        )
+                    }
+                }
+            }
+        };
+        let end = self.check_bounds(end, input.len())?;
+
+        Ok(start..=end)
+    }
+}
+
+impl<T> ContextFreeArgParser<RangeInclusive<T>> for IntRangeArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<RangeInclusive<T>> {
+        match self.parse_range(input) {
+            Ok(range) => ArgParseRes::Parsed(range),
+            Err(failure) => failure,
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        let start_len = int_literal_prefix_len(prefix);
+        if start_len == 0 {
+            return vec![];
+        }
+
+        let (start, operator) = prefix.split_at(start_len);
+        OPERATORS
+            .iter()
+            .rev()
+            .filter(|op| op.starts_with(operator) && op.len() > operator.len())
+            .map(|op| format!("{}{}", start, op))
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let min = self.bounds.radix.format(self.bounds.min);
+        let max = self.bounds.radix.format(self.bounds.max);
+
+        match &self.bounds.name {
+            Some(name) => vec![format!("<{}: {}..={}>", name, min, max)],
+            None => vec![format!("<{}..={}>", min, max)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        int_range, int_range_with_name, int_range_within,
+        int_range_within_and_name,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::Radix;
+
+    #[test]
+    fn u8_dec() {
+        let parser = int_range::<u8>(Radix::Dec);
+        let expected_hint = &["<0..=255>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0..10", 0..=9);
+        check_parse("0..=10", 0..=10);
+        check_parse("3+4", 3..=6);
+        check_parse("0..=255", 0..=255);
+        check_parse("255+1", 255..=255);
+        check_parse("0x10..0x20", 16..=31);
+        check_parse("7..=7", 7..=7);
+
+        check_failure("", 0, expected_hint);
+        check_failure("..10", 0, expected_hint);
+        check_failure("10", 2, expected_hint);
+        check_failure("10.", 3, expected_hint);
+        check_failure("10-20", 2, expected_hint);
+        check_failure("10..", 4, expected_hint);
+        check_failure("10..x", 4, expected_hint);
+        check_failure("10..2x", 5, expected_hint);
+        check_failure("10..=", 5, expected_hint);
+        check_failure("10+", 3, expected_hint);
+        check_failure("256..300", 3, expected_hint);
+        check_failure("10..300", 7, expected_hint);
+        check_failure("10..10", 6, &["end must be greater than start"]);
+        check_failure("10..=9", 6, &["end must not be less than start"]);
+        check_failure("10+0", 4, &["length must be positive"]);
+        check_failure("200+100", 7, &["range is too long"]);
+
+        check_suggestions("", &[]);
+        check_suggestions("x", &[]);
+        check_suggestions("10", &["10+", "10..", "10..="]);
+        check_suggestions("10.", &["10..", "10..="]);
+        check_suggestions("10..", &["10..="]);
+        check_suggestions("10..=", &[]);
+        check_suggestions("10..=2", &[]);
+        check_suggestions("10+", &[]);
+    }
+
+    #[test]
+    fn u32_hex_within_limits() {
+        let parser =
+            int_range_within_and_name(0x1000u32, 0x1fff, Radix::Hex, "region");
+        let expected_hint = &["<region: 0x1000..=0x1fff>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0x1000..0x2000", 0x1000..=0x1fff);
+        check_parse("0x1800+0x100", 0x1800..=0x18ff);
+        check_parse("4096..=4096", 0x1000..=0x1000);
+
+        check_failure("0xfff..0x1100", 5, &["min region: 0x1000"]);
+        check_failure("0x1000..0x2001", 14, &["max region: 0x1fff"]);
+        check_failure("0x1f00+0x101", 12, &["max region: 0x1fff"]);
+    }
+
+    #[test]
+    fn signed_and_named() {
+        let (check_hint, _, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                int_range_with_name::<i8, _>(Radix::Dec, "offsets"),
+            );
+
+        check_hint(&["<offsets: -128..=127>"]);
+        check_parse("-10..10", -10..=9);
+        check_parse("-128..=-128", -128..=-128);
+        check_parse("120+8", 120..=127);
+        check_failure("120+9", 5, &["range is too long"]);
+
+        let (check_hint, _, check_parse, _) = build_cf_parse_checkers(
+            "parser",
+            int_range_within(-4i64, 4, Radix::Dec),
+        );
+
+        check_hint(&["<-4..=4>"]);
+        check_parse("-4..5", -4..=4);
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `name=value` arguments, where the value parser is selected by the name,
+//! like `baud=115200` or `parity=even`.
+
+use super::keyword_set::{keyword_set, KeywordSetArgParser};
+use super::{
+    prefix_suggestion_details, ArgParseRes, ContextFreeArgParser,
+    SuggestionDetails,
+};
+
+/// Parses `key=value`, where `key` is one of a fixed set of keys, and `value`
+/// is parsed with the parser associated with that key.  Produces the key and
+/// the value.  Constructed with [`key_value()`].
+///
+/// All value parsers produce the same type, so, when keys have values of
+/// different types, [`ContextFreeArgParser::map()`] them into variants of an
+/// enum.
+pub struct KeyValueArgParser<Res> {
+    keys: KeywordSetArgParser,
+    /// Value parser for every key, in the same order as the keys.
+    entries: Vec<(String, Box<dyn ContextFreeArgParser<Res>>)>,
+}
+
+/// Separates the key from the value.
+const SEPARATOR: char = '=';
+
+/// Parses `key=value` arguments, where `entries` pair every key with the
+/// parser for its value.  Keys are suggested with the `=` already appended, so
+/// that the value can be typed right away.
+///
+/// Keys can not contain `=` or whitespace.
+pub fn key_value<Key, Entries, Res>(entries: Entries) -> KeyValueArgParser<Res>
+where
+    Key: ToString,
+    Entries: IntoIterator<Item = (Key, Box<dyn ContextFreeArgParser<Res>>)>,
+{
+    let entries = entries
+        .into_iter()
+        .map(|(key, parser)| (key.to_string(), parser))
+        .collect::<Vec<_>>();
+
+    if let Some((key, _)) = entries.iter().find(|(key, _)| {
+        key.contains(SEPARATOR) || key.contains(char::is_whitespace)
+    }) {
+        panic!(
+            "Keys should not contain '{}' or whitespace.\n\
+             key: '{}'",
+            SEPARATOR, key
+        );
+    }
+
+    let keys = keyword_set(entries.iter().map(|(key, _)| key));
+
+    KeyValueArgParser { keys, entries }
+}
+
+impl<Res> KeyValueArgParser<Res> {
+    fn value_parser(&self, key: &str) -> &dyn ContextFreeArgParser<Res> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, parser)| parser.as_ref())
+            // `self.keys` only accepts keys from `self.entries`.
+            .unwrap()
+    }
+
+    /// `baud=<rate>` or `parity=none|odd|even`.
+    fn key_hint(&self, key: &str) -> String {
+        let value_hint = self.value_parser(key).hint().join("|");
+        format!("{}{}{}", key, SEPARATOR, value_hint)
+    }
+}
+
+impl<Res> ContextFreeArgParser<(String, Res)> for KeyValueArgParser<Res> {
+    fn parse(&self, input: &str) -> ArgParseRes<(String, Res)> {
+        let (key, value) = match input.split_once(SEPARATOR) {
+            Some((key, value)) => (key, Some(value)),
+            None => (input, None),
+        };
+
+        let key = match self.keys.parse(key) {
+            ArgParseRes::Parsed(key) => key,
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            } => {
+                return ArgParseRes::Failed {
+                    parsed_up_to,
+                    reason,
+                }
+            }
+        };
+
+        let value = match value {
+            Some(value) => value,
+            None => {
+                return ArgParseRes::Failed {
+                    parsed_up_to: input.len(),
+                    reason: vec![self.key_hint(&key)],
+                }
+            }
+        };
+
+        let value_start = key.len() + SEPARATOR.len_utf8();
+        match self.value_parser(&key).parse(value) {
+            ArgParseRes::Parsed(value) => ArgParseRes::Parsed((key, value)),
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            } => ArgParseRes::Failed {
+                parsed_up_to: value_start + parsed_up_to,
+                reason,
+            },
+        }
+    }
+
+    /// Before the `=` suggestions are the matching keys, followed by the `=`.
+    /// After it they come from the value parser of the entered key, following
+    /// the key.
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        match prefix.split_once(SEPARATOR) {
+            None => self
+                .keys
+                .suggestion(prefix)
+                .into_iter()
+                .map(|key| format!("{}{}", key, SEPARATOR))
+                .collect(),
+            Some((key, value)) => match self.keys.parse(key) {
+                ArgParseRes::Parsed(key) => self
+                    .value_parser(&key)
+                    .suggestion(value)
+                    .into_iter()
+                    .map(|value| format!("{}{}{}", key, SEPARATOR, value))
+                    .collect(),
+                ArgParseRes::Failed { .. } => vec![],
+            },
+        }
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        match prefix.split_once(SEPARATOR) {
+            None => self
+                .keys
+                .suggestion_details(prefix)
+                .into_iter()
+                .map(|(key, details)| {
+                    (format!("{}{}", key, SEPARATOR), details)
+                })
+                .collect(),
+            Some((key, value)) => match self.keys.parse(key) {
+                ArgParseRes::Parsed(key) => prefix_suggestion_details(
+                    &format!("{}{}", key, SEPARATOR),
+                    self.value_parser(&key).suggestion_details(value),
+                ),
+                ArgParseRes::Failed { .. } => vec![],
+            },
+        }
+    }
+
+    fn hint(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|(key, _)| self.key_hint(key))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::key_value;
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{
+        keyword_set, prim_int_with_name, ContextFreeArgParser,
+    };
+
+    #[derive(PartialEq, Clone, Debug)]
+    enum Setting {
+        Baud(u32),
+        Parity(String),
+    }
+
+    #[test]
+    fn settings() {
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                key_value(vec![
+                    (
+                        "baud",
+                        Box::new(
+                            prim_int_with_name::<u32, _>("rate")
+                                .map(Setting::Baud),
+                        )
+                            as Box<dyn ContextFreeArgParser<_>>,
+                    ),
+                    (
+                        "parity",
+                        Box::new(
+                            keyword_set(["none", "odd", "even"])
+                                .map(Setting::Parity),
+                        ),
+                    ),
+                ])
+                .map(|(_, setting)| setting),
+            );
+
+        check_hint(&["baud=<rate: 0-4294967295>", "parity=none|odd|even"]);
+
+        check_parse("baud=9600", Setting::Baud(9600));
+        check_parse("parity=odd", Setting::Parity("odd".to_string()));
+
+        check_failure("speed=9600", 0, &["baud", "parity"]);
+        check_failure("baud", 4, &["baud=<rate: 0-4294967295>"]);
+        check_failure("baud=96x", 7, &["<rate: 0-4294967295>"]);
+        check_failure("parity=", 7, &["none", "odd", "even"]);
+
+        check_suggestions("", &["baud=", "parity="]);
+        check_suggestions("p", &["parity="]);
+        check_suggestions(
+            "parity=",
+            &["parity=none", "parity=odd", "parity=even"],
+        );
+        check_suggestions("parity=e", &["parity=even"]);
+        check_suggestions("speed=", &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys should not contain '='")]
+    fn invalid_key() {
+        key_value(vec![(
+            "a=b",
+            Box::new(keyword_set(["c"])) as Box<dyn ContextFreeArgParser<_>>,
+        )]);
+    }
+}
@@ -14,21 +14,239 @@
 
 use std::string::ToString;
 
-use super::{ArgParseRes, ContextFreeArgParser};
+use super::{ArgParseRes, ContextFreeArgParser, SuggestionDetails};
+
+use crate::input::fuzzy_match::fuzzy_match;
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct KeywordSetArgParser {
     keywords: Vec<String>,
     hints: Vec<String>,
+    /// One line description for every keyword in `keywords`, at the same
+    /// index.
+    descriptions: Vec<Option<String>>,
+    /// Compare keywords ignoring case.  See [`Self::with_case_folding()`].
+    case_folding: bool,
+    /// Accept unambiguous prefixes.  See [`Self::with_unique_prefix()`].
+    unique_prefix: bool,
+    /// Suggest keywords that contain the prefix characters anywhere.  See
+    /// [`Self::with_fuzzy_suggestions()`].
+    fuzzy: bool,
+    /// Alternative spellings, paired with indices of the corresponding
+    /// keywords in `keywords`.  See [`Self::with_aliases()`].
+    aliases: Vec<(String, usize)>,
 }
 
 impl KeywordSetArgParser {
     fn new(keywords: Vec<String>, hints: Vec<String>) -> Self {
+        let descriptions = vec![None; keywords.len()];
+        Self::new_with_descriptions(keywords, hints, descriptions)
+    }
+
+    fn new_with_descriptions(
+        keywords: Vec<String>,
+        hints: Vec<String>,
+        descriptions: Vec<Option<String>>,
+    ) -> Self {
         if keywords.is_empty() {
             panic!("`keywords` should not be empty");
         }
 
-        Self { keywords, hints }
+        Self {
+            keywords,
+            hints,
+            descriptions,
+            case_folding: false,
+            unique_prefix: false,
+            fuzzy: false,
+            aliases: vec![],
+        }
+    }
+
+    /// Accepts every one of `aliases` as an alternative spelling of
+    /// `keyword`, like `exit` and `q` for `quit`.  Parsed value is always the
+    /// keyword itself.  Aliases are not suggested and are not included in the
+    /// hint, but failure reasons mention aliases that match the input.  To
+    /// associate other values with keywords, use
+    /// [`ContextFreeArgParser::map()`].
+    ///
+    /// Panics if `keyword` is not one of the keywords of this parser, or if it
+    /// is listed more than once.
+    pub fn with_aliases<Alias, Aliases>(
+        mut self,
+        keyword: &str,
+        aliases: Aliases,
+    ) -> Self
+    where
+        Alias: ToString,
+        Aliases: IntoIterator<Item = Alias>,
+    {
+        let index = self.keyword_index(keyword);
+        self.aliases
+            .extend(aliases.into_iter().map(|a| (a.to_string(), index)));
+        self
+    }
+
+    /// Sets a one line description of `keyword`, same as
+    /// [`keyword_set_with_descriptions()`] does for all the keywords at once.
+    ///
+    /// Panics if `keyword` is not one of the keywords of this parser, or if it
+    /// is listed more than once.
+    pub fn with_description<Description>(
+        mut self,
+        keyword: &str,
+        description: Description,
+    ) -> Self
+    where
+        Description: ToString,
+    {
+        let index = self.keyword_index(keyword);
+        self.descriptions[index] = Some(description.to_string());
+        self
+    }
+
+    /// Accepts keywords typed in any case, like `RESET` or `Reset` for
+    /// `reset`.  Comparison uses Unicode lower case mapping.  Parsed value is
+    /// always the keyword as it was specified.
+    pub fn with_case_folding(self) -> Self {
+        Self {
+            case_folding: true,
+            ..self
+        }
+    }
+
+    /// Accepts a prefix of a keyword, when it matches only one keyword, like
+    /// `res` for `reset`.  Prefixes that match several keywords are reported
+    /// as failures, listing all the candidates.  Parsed value is always the
+    /// complete keyword.
+    pub fn with_unique_prefix(self) -> Self {
+        Self {
+            unique_prefix: true,
+            ..self
+        }
+    }
+
+    /// Suggests keywords that contain all the characters of the typed prefix,
+    /// in order, but not necessarily next to each other.  Like `ctrl` for
+    /// `UART_CTRL_REG`.  Suggestions are ordered by how well they match, with
+    /// matches at word starts and contiguous matches ranked higher.  Matching
+    /// ignores case.  Only affects suggestions,
+    /// [`ContextFreeArgParser::parse()`] still requires a keyword.
+    pub fn with_fuzzy_suggestions(self) -> Self {
+        Self {
+            fuzzy: true,
+            ..self
+        }
+    }
+
+    /// Suggestions for the fuzzy mode, best matches first.  Every suggestion
+    /// holds positions of the characters that matched the prefix.
+    fn fuzzy_suggestions(&self, prefix: &str) -> Vec<(String, Vec<usize>)> {
+        let mut matches = self
+            .keywords
+            .iter()
+            .filter(|k| !self.matches(prefix, k))
+            .filter_map(|k| fuzzy_match(prefix, k, true).map(|m| (k, m)))
+            .collect::<Vec<_>>();
+
+        // Sort is stable, so equally good matches stay in the keywords order.
+        matches.sort_by_key(|(_, m)| -m.score);
+
+        matches
+            .into_iter()
+            .map(|(k, m)| (k.clone(), m.positions))
+            .collect()
+    }
+
+    /// Panics if `keyword` is not one of the keywords of this parser, or if it
+    /// is listed more than once, as it would be unclear which one is meant.
+    fn keyword_index(&self, keyword: &str) -> usize {
+        let mut indices = self
+            .keywords
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| *k == keyword)
+            .map(|(i, _)| i);
+
+        match (indices.next(), indices.next()) {
+            (Some(index), None) => index,
+            (None, _) => panic!(
+                "`keyword` is not one of the keywords: '{}'\n\
+                 Keywords: {}",
+                keyword,
+                self.keywords.join(", ")
+            ),
+            (Some(_), Some(_)) => panic!(
+                "`keyword` is listed more than once: '{}'\n\
+                 Keywords: {}",
+                keyword,
+                self.keywords.join(", ")
+            ),
+        }
+    }
+
+    /// All the accepted spellings, keywords first, followed by the aliases.
+    /// Every spelling is paired with an index of the corresponding keyword.
+    fn spellings(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.keywords
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (k.as_str(), i))
+            .chain(self.aliases.iter().map(|(a, i)| (a.as_str(), *i)))
+    }
+
+    /// Indices of keywords that have at least one spelling that satisfies
+    /// `pred`.
+    fn candidates(&self, pred: impl Fn(&str) -> bool) -> Vec<usize> {
+        let mut res = vec![];
+        for (s, i) in self.spellings() {
+            if pred(s) && !res.contains(&i) {
+                res.push(i);
+            }
+        }
+        res
+    }
+
+    fn chars_eq(&self, c1: char, c2: char) -> bool {
+        c1 == c2
+            || (self.case_folding && c1.to_lowercase().eq(c2.to_lowercase()))
+    }
+
+    /// Number of characters at the start of `input` that match `keyword`.
+    fn matched_len(&self, input: &str, keyword: &str) -> usize {
+        input
+            .chars()
+            .zip(keyword.chars())
+            .take_while(|(c1, c2)| self.chars_eq(*c1, *c2))
+            .count()
+    }
+
+    fn matches(&self, input: &str, keyword: &str) -> bool {
+        input.chars().count() == keyword.chars().count()
+            && self.matched_len(input, keyword) == input.chars().count()
+    }
+
+    fn is_prefix_of(&self, prefix: &str, keyword: &str) -> bool {
+        self.matched_len(prefix, keyword) == prefix.chars().count()
+    }
+
+    /// Description of the specified keyword, if one was provided.
+    pub fn description(&self, keyword: &str) -> Option<&str> {
+        self.keywords
+            .iter()
+            .position(|k| k == keyword)
+            .and_then(|i| self.descriptions[i].as_deref())
+    }
+
+    /// Keywords paired with their descriptions, in the order the keywords
+    /// were specified.  Keywords without descriptions are skipped.  See
+    /// [`crate::commands::help::keywords_usage()`] for a way to format them.
+    pub fn descriptions(&self) -> Vec<(&str, &str)> {
+        self.keywords
+            .iter()
+            .zip(self.descriptions.iter())
+            .filter_map(|(k, d)| d.as_deref().map(|d| (k.as_str(), d)))
+            .collect()
     }
 }
 
@@ -63,42 +281,118 @@ where
     KeywordSetArgParser::new(keywords, hints)
 }
 
-fn common_prefix_len(s1: &str, s2: &str) -> usize {
-    let mut matched = 0;
-    for (res_char, next_char) in s1.chars().zip(s2.chars()) {
-        if res_char != next_char {
-            break;
-        }
-        matched += 1;
-    }
-    matched
+/// Every keyword is paired with a one line description, that frontends could
+/// show next to the suggestions.
+pub fn keyword_set_with_descriptions<Keyword, Description, Keywords>(
+    keywords: Keywords,
+) -> KeywordSetArgParser
+where
+    Keyword: ToString,
+    Description: ToString,
+    Keywords: IntoIterator<Item = (Keyword, Description)>,
+{
+    let (keywords, descriptions): (Vec<_>, Vec<_>) = keywords
+        .into_iter()
+        .map(|(k, d)| (k.to_string(), Some(d.to_string())))
+        .unzip();
+    let hints = keywords.clone();
+    KeywordSetArgParser::new_with_descriptions(keywords, hints, descriptions)
+}
+
+/// Combines [`keyword_set_with_descriptions()`] and
+/// [`keyword_set_with_hint()`].
+pub fn keyword_set_with_descriptions_and_hint<
+    Keyword,
+    Description,
+    Keywords,
+    Hint,
+    Hints,
+>(
+    keywords: Keywords,
+    hints: Hints,
+) -> KeywordSetArgParser
+where
+    Keyword: ToString,
+    Description: ToString,
+    Keywords: IntoIterator<Item = (Keyword, Description)>,
+    Hint: ToString,
+    Hints: IntoIterator<Item = Hint>,
+{
+    let (keywords, descriptions): (Vec<_>, Vec<_>) = keywords
+        .into_iter()
+        .map(|(k, d)| (k.to_string(), Some(d.to_string())))
+        .unzip();
+    let hints = hints.into_iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    KeywordSetArgParser::new_with_descriptions(keywords, hints, descriptions)
 }
 
 impl ContextFreeArgParser<String> for KeywordSetArgParser {
     fn parse(&self, input: &str) -> ArgParseRes<String> {
-        for k in &self.keywords {
-            if input == k {
-                return ArgParseRes::Parsed(k.clone());
+        if let Some((_, i)) = self.spellings().find(|(s, _)| input == *s) {
+            return ArgParseRes::Parsed(self.keywords[i].clone());
+        }
+
+        let mut candidates = self.candidates(|s| self.matches(input, s));
+
+        if candidates.is_empty() && self.unique_prefix && !input.is_empty() {
+            candidates = self.candidates(|s| self.is_prefix_of(input, s));
+        }
+
+        match candidates.as_slice() {
+            [] => (),
+            [i] => return ArgParseRes::Parsed(self.keywords[*i].clone()),
+            _ => {
+                let candidates = candidates
+                    .iter()
+                    .map(|i| self.keywords[*i].as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return ArgParseRes::Failed {
+                    parsed_up_to: input.chars().count(),
+                    reason: vec![format!("ambiguous: {}", candidates)],
+                };
             }
         }
 
         let longest_match = self
-            .keywords
-            .iter()
-            .map(|k| common_prefix_len(input, &k))
+            .spellings()
+            .map(|(s, _)| self.matched_len(input, s))
             .max()
             .unwrap_or(0);
 
+        let mut reason = self.hints.clone();
+        if !input.is_empty() {
+            reason.extend(
+                self.aliases
+                    .iter()
+                    .filter(|(alias, _)| self.is_prefix_of(input, alias))
+                    .map(|(alias, i)| {
+                        format!("{} (alias of {})", alias, self.keywords[*i])
+                    }),
+            );
+        }
+
         ArgParseRes::Failed {
             parsed_up_to: longest_match,
-            reason: self.hints.clone(),
+            reason,
         }
     }
 
     fn suggestion(&self, prefix: &str) -> Vec<String> {
+        if self.fuzzy {
+            return self
+                .fuzzy_suggestions(prefix)
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect();
+        }
+
         self.keywords
             .iter()
-            .filter(|k| k.starts_with(prefix) && k.len() > prefix.len())
+            .filter(|k| {
+                self.is_prefix_of(prefix, k)
+                    && k.chars().count() > prefix.chars().count()
+            })
             .cloned()
             .collect()
     }
@@ -106,11 +400,48 @@ impl ContextFreeArgParser<String> for KeywordSetArgParser {
     fn hint(&self) -> Vec<String> {
         self.hints.clone()
     }
+
+    /// Suggested keywords that have descriptions, and, in the fuzzy mode,
+    /// keywords that matched the prefix.
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        let suggestions = if self.fuzzy {
+            self.fuzzy_suggestions(prefix)
+        } else {
+            self.suggestion(prefix)
+                .into_iter()
+                .map(|k| (k, vec![]))
+                .collect()
+        };
+
+        suggestions
+            .into_iter()
+            .map(|(k, matched)| {
+                let description = self.description(&k).map(str::to_string);
+                (
+                    k,
+                    SuggestionDetails {
+                        description,
+                        matched,
+                    },
+                )
+            })
+            .filter(|(_, details)| details != &SuggestionDetails::default())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{keyword_set, keyword_set_with_hint, ContextFreeArgParser};
+    use super::{
+        keyword_set, keyword_set_with_descriptions,
+        keyword_set_with_descriptions_and_hint, keyword_set_with_hint,
+        ContextFreeArgParser, SuggestionDetails,
+    };
+
+    use crate::input::arg_parser::Arg2Parser;
 
     use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
 
@@ -194,6 +525,289 @@ mod tests {
         check_suggestions("a", &[]);
     }
 
+    #[test]
+    fn with_descriptions() {
+        let ks = &[("full", "Both directions at once."), ("half", "One way.")];
+        let expected_hint = &["full", "half"];
+
+        let parser = keyword_set_with_descriptions(ks.iter().copied());
+
+        assert_eq!(
+            parser.description("full"),
+            Some("Both directions at once.")
+        );
+        assert_eq!(parser.description("hal"), None);
+        assert_eq!(
+            parser.descriptions(),
+            vec![("full", "Both directions at once."), ("half", "One way.")]
+        );
+        assert_eq!(keyword_set(["full"]).description("full"), None);
+        assert!(keyword_set(["full"]).descriptions().is_empty());
+        assert_eq!(
+            keyword_set(["full", "half"])
+                .with_description("half", "One way.")
+                .descriptions(),
+            vec![("half", "One way.")]
+        );
+
+        let described = |k: &str, d: &str| {
+            let details = SuggestionDetails {
+                description: Some(d.to_string()),
+                ..SuggestionDetails::default()
+            };
+            (k.to_string(), details)
+        };
+        assert_eq!(
+            parser.suggestion_details(""),
+            vec![
+                described("full", "Both directions at once."),
+                described("half", "One way."),
+            ]
+        );
+        assert_eq!(
+            Arg2Parser::suggestion_details(
+                &parser.clone().map(|k| k.len()).adapt(),
+                &0,
+                "h",
+            ),
+            vec![described("half", "One way.")]
+        );
+        assert!(keyword_set(["full"]).suggestion_details("").is_empty());
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("full", "full".to_string());
+        check_parse("half", "half".to_string());
+
+        check_failure("ful", 3, expected_hint);
+
+        check_suggestions("", &["full", "half"]);
+        check_suggestions("h", &["half"]);
+
+        let parser = keyword_set_with_descriptions_and_hint(
+            ks.iter().copied(),
+            ["<duplex>"],
+        );
+        assert_eq!(parser.description("half"), Some("One way."));
+
+        let (check_hint, _check_suggestions, _check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["<duplex>"]);
+        check_failure("x", 0, &["<duplex>"]);
+    }
+
+    #[test]
+    fn case_folding_and_prefix() {
+        let ks = &["reset", "Resume", "half", "halt", "Straße"];
+        let expected_hint = ks;
+
+        let parser = keyword_set(ks).with_case_folding();
+
+        let (_check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("reset", "reset".to_string());
+        check_parse("RESET", "reset".to_string());
+        check_parse("resume", "Resume".to_string());
+        check_parse("STRAßE", "Straße".to_string());
+        check_parse("straße", "Straße".to_string());
+
+        check_failure("res", 3, expected_hint);
+        check_failure("RESETX", 5, expected_hint);
+
+        check_suggestions("RE", &["reset", "Resume"]);
+        check_suggestions("Reset", &[]);
+
+        let parser = keyword_set(ks).with_unique_prefix();
+
+        let (_check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("rese", "reset".to_string());
+        check_parse("Res", "Resume".to_string());
+        check_parse("half", "half".to_string());
+
+        check_failure("RES", 1, expected_hint);
+        check_failure("hal", 3, &["ambiguous: half, halt"]);
+        check_failure("", 0, expected_hint);
+        check_failure("rex", 2, expected_hint);
+
+        check_suggestions("res", &["reset"]);
+
+        let parser = keyword_set(ks).with_case_folding().with_unique_prefix();
+
+        let (_check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("RESE", "reset".to_string());
+        check_parse("RESU", "Resume".to_string());
+        check_parse("HALF", "half".to_string());
+        check_parse("halt", "halt".to_string());
+
+        check_failure("re", 2, &["ambiguous: reset, Resume"]);
+        check_failure("HAL", 3, &["ambiguous: half, halt"]);
+
+        check_suggestions("re", &["reset", "Resume"]);
+    }
+
+    #[test]
+    fn fuzzy_suggestions() {
+        let ks = &["UART_CTRL_REG", "UART_DATA", "CAN_TX_RX_LEN", "SPI_CTRL"];
+        let expected_hint = ks;
+
+        let parser = keyword_set(ks).with_fuzzy_suggestions();
+
+        let matched = |entries: &[(&str, &[usize])]| {
+            entries
+                .iter()
+                .map(|(k, matched)| {
+                    let details = SuggestionDetails {
+                        matched: matched.to_vec(),
+                        ..SuggestionDetails::default()
+                    };
+                    (k.to_string(), details)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            parser.suggestion_details("ctrl"),
+            matched(&[
+                ("SPI_CTRL", &[4, 5, 6, 7]),
+                ("UART_CTRL_REG", &[5, 6, 7, 8]),
+                ("CAN_TX_RX_LEN", &[0, 4, 7, 10]),
+            ])
+        );
+        assert_eq!(
+            parser.suggestion_details("UART_"),
+            matched(&[
+                ("UART_CTRL_REG", &[0, 1, 2, 3, 4]),
+                ("UART_DATA", &[0, 1, 2, 3, 4]),
+            ])
+        );
+        assert_eq!(parser.suggestion_details(""), vec![]);
+
+        let (_check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("SPI_CTRL", "SPI_CTRL".to_string());
+        check_failure("ctrl", 0, expected_hint);
+
+        check_suggestions("", ks);
+        check_suggestions(
+            "ctrl",
+            &["SPI_CTRL", "UART_CTRL_REG", "CAN_TX_RX_LEN"],
+        );
+        check_suggestions(
+            "CTRL",
+            &["SPI_CTRL", "UART_CTRL_REG", "CAN_TX_RX_LEN"],
+        );
+        check_suggestions("ud", &["UART_DATA"]);
+        check_suggestions("SPI_CTRL", &[]);
+        check_suggestions("xyz", &[]);
+
+        let parser =
+            keyword_set(ks).with_fuzzy_suggestions().with_case_folding();
+        assert_eq!(
+            parser.suggestion(""),
+            ks.iter().map(|k| k.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(parser.suggestion("uartd"), vec!["UART_DATA".to_string()]);
+        assert_eq!(parser.suggestion("spi_ctrl"), Vec::<String>::new());
+
+        // Without fuzzy matching suggestions start with the prefix, and there
+        // is nothing to add.
+        let parser = keyword_set(ks);
+        assert_eq!(parser.suggestion("UART_").len(), 2);
+        assert_eq!(parser.suggestion_details("UART_"), vec![]);
+    }
+
+    #[test]
+    fn aliases() {
+        let ks = &["quit", "help", "status"];
+        let expected_hint = ks;
+
+        let parser = keyword_set(ks)
+            .with_aliases("quit", ["exit", "q"])
+            .with_aliases("status", ["st"]);
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser.clone());
+
+        check_hint(expected_hint);
+
+        check_parse("quit", "quit".to_string());
+        check_parse("exit", "quit".to_string());
+        check_parse("q", "quit".to_string());
+        check_parse("st", "status".to_string());
+
+        check_failure("", 0, expected_hint);
+        check_failure(
+            "ex",
+            2,
+            &["quit", "help", "status", "exit (alias of quit)"],
+        );
+        check_failure("exits", 4, expected_hint);
+        check_failure("sta", 3, expected_hint);
+
+        check_suggestions("", &["quit", "help", "status"]);
+        check_suggestions("q", &["quit"]);
+        check_suggestions("e", &[]);
+
+        let parser = parser.with_case_folding().with_unique_prefix();
+
+        let (_check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("EXIT", "quit".to_string());
+        check_parse("ex", "quit".to_string());
+        check_parse("qu", "quit".to_string());
+        check_parse("s", "status".to_string());
+
+        check_failure("x", 0, expected_hint);
+
+        let parser = keyword_set(["start", "stop"])
+            .with_aliases("start", ["go"])
+            .with_aliases("stop", ["gone"])
+            .with_unique_prefix();
+
+        let (_check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("go", "start".to_string());
+        check_parse("gon", "stop".to_string());
+        check_failure("st", 2, &["ambiguous: start, stop"]);
+        check_failure("g", 1, &["ambiguous: start, stop"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`keyword` is not one of the keywords: 'stop'")]
+    fn alias_of_unknown_keyword() {
+        keyword_set(["quit"]).with_aliases("stop", ["halt"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`keyword` is not one of the keywords: 'stop'")]
+    fn description_of_unknown_keyword() {
+        keyword_set(["quit"]).with_description("stop", "Stops.");
+    }
+
+    #[test]
+    #[should_panic(expected = "`keyword` is listed more than once: 'quit'")]
+    fn alias_of_repeated_keyword() {
+        keyword_set(["quit", "stop", "quit"]).with_aliases("quit", ["q"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`keyword` is listed more than once: 'quit'")]
+    fn description_of_repeated_keyword() {
+        keyword_set(["quit", "quit"]).with_description("quit", "Exits.");
+    }
+
     #[test]
     fn map() {
         #[derive(PartialEq, Clone, Debug)]
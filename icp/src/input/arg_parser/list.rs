@@ -0,0 +1,235 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lists of values inside a single argument, like `1,3,5-7`.
+
+use std::marker::PhantomData;
+
+use super::{
+    prefix_suggestion_details, ArgParseRes, ContextFreeArgParser,
+    SuggestionDetails,
+};
+
+/// Splits the argument on a separator and parses every element with the same
+/// parser.  Constructed with [`list_of()`].
+pub struct ListArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    parser: Parser,
+    separator: char,
+    _res: PhantomData<fn() -> Res>,
+}
+
+/// Parses a list of elements separated by `separator`.  Empty elements are
+/// passed to the element parser, which would normally reject them.
+///
+/// `separator` can not be a whitespace character, as arguments never contain
+/// whitespace.
+pub fn list_of<Parser, Res>(
+    parser: Parser,
+    separator: char,
+) -> ListArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    if separator.is_whitespace() {
+        panic!(
+            "`separator` should not be a whitespace character.\n\
+             separator: {:?}",
+            separator
+        );
+    }
+
+    ListArgParser {
+        parser,
+        separator,
+        _res: PhantomData,
+    }
+}
+
+impl<Parser, Res> ListArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    /// Splits `prefix` into the complete elements, with the trailing
+    /// separator, and the element that `prefix` ends with.
+    fn split_current_element<'a>(&self, prefix: &'a str) -> (&'a str, &'a str) {
+        match prefix.rfind(self.separator) {
+            Some(i) => prefix.split_at(i + self.separator.len_utf8()),
+            None => ("", prefix),
+        }
+    }
+}
+
+impl<Parser, Res> ContextFreeArgParser<Vec<Res>> for ListArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<Vec<Res>> {
+        let mut res = vec![];
+        let mut start = 0;
+
+        for element in input.split(self.separator) {
+            match self.parser.parse(element) {
+                ArgParseRes::Parsed(value) => res.push(value),
+                ArgParseRes::Failed {
+                    parsed_up_to,
+                    reason,
+                } => {
+                    return ArgParseRes::Failed {
+                        parsed_up_to: start + parsed_up_to,
+                        reason,
+                    }
+                }
+            }
+
+            start += element.len() + self.separator.len_utf8();
+        }
+
+        ArgParseRes::Parsed(res)
+    }
+
+    /// Suggestions are for the element that `prefix` ends with, following
+    /// all the preceding elements.
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        let (before, current) = self.split_current_element(prefix);
+        self.parser
+            .suggestion(current)
+            .into_iter()
+            .map(|suggestion| format!("{}{}", before, suggestion))
+            .collect()
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        let (before, current) = self.split_current_element(prefix);
+        prefix_suggestion_details(
+            before,
+            self.parser.suggestion_details(current),
+        )
+    }
+
+    /// `<channel>` becomes `<channel>,...`.
+    fn hint(&self) -> Vec<String> {
+        self.parser
+            .hint()
+            .into_iter()
+            .map(|h| format!("{}{}...", h, self.separator))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::list_of;
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{
+        int_range, keyword_set, prim_int_with_name, ContextFreeArgParser,
+        Radix, SuggestionDetails,
+    };
+
+    #[test]
+    fn numbers() {
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                list_of(prim_int_with_name::<u8, _>("channel"), ','),
+            );
+
+        check_hint(&["<channel: 0-255>,..."]);
+
+        check_parse("3", vec![3]);
+        check_parse("1,3,5", vec![1, 3, 5]);
+
+        check_failure("", 0, &["<channel: 0-255>"]);
+        check_failure("1,,3", 2, &["<channel: 0-255>"]);
+        check_failure("1,3,", 4, &["<channel: 0-255>"]);
+        check_failure("1,35x,7", 4, &["<channel: 0-255>"]);
+
+        check_suggestions("1,", &[]);
+    }
+
+    #[test]
+    fn keywords() {
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                list_of(keyword_set(["rx", "tx", "err"]), '+'),
+            );
+
+        check_hint(&["rx+...", "tx+...", "err+..."]);
+
+        check_parse("rx+err", vec!["rx".to_string(), "err".to_string()]);
+        check_failure("rx+e", 4, &["rx", "tx", "err"]);
+
+        check_suggestions("", &["rx", "tx", "err"]);
+        check_suggestions("rx+", &["rx+rx", "rx+tx", "rx+err"]);
+        check_suggestions("rx+t", &["rx+tx"]);
+        check_suggestions("rx+tx+e", &["rx+tx+err"]);
+    }
+
+    #[test]
+    fn ranges() {
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                list_of(int_range::<u8>(Radix::Dec), ','),
+            );
+
+        check_hint(&["<0..=255>,..."]);
+
+        check_parse("1..3,7+2", vec![1..=2, 7..=8]);
+        check_failure("1..3,7..", 8, &["<0..=255>"]);
+
+        check_suggestions("1..3,7", &["1..3,7+", "1..3,7..", "1..3,7..="]);
+        check_suggestions("1..3,7.", &["1..3,7..", "1..3,7..="]);
+        check_suggestions("1..3,", &[]);
+    }
+
+    #[test]
+    fn suggestion_details() {
+        let parser = list_of(
+            keyword_set(["UART_CTRL", "SPI_CTRL"]).with_fuzzy_suggestions(),
+            '+',
+        );
+
+        let matched = |entries: &[(&str, &[usize])]| {
+            entries
+                .iter()
+                .map(|(k, matched)| {
+                    let details = SuggestionDetails {
+                        matched: matched.to_vec(),
+                        ..SuggestionDetails::default()
+                    };
+                    (k.to_string(), details)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            parser.suggestion_details("SPI_CTRL+uart"),
+            matched(&[("SPI_CTRL+UART_CTRL", &[9, 10, 11, 12])]),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "`separator` should not be a whitespace")]
+    fn whitespace_separator() {
+        list_of(keyword_set(["a"]), ' ');
+    }
+}
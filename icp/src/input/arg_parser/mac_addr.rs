@@ -0,0 +1,241 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsers for 48 bit MAC addresses and 64 bit EUI-64 identifiers.
+//!
+//! Both accept bytes separated by colons or hyphens, like `00:1a:2b:3c:4d:5e`
+//! or `00-1a-2b-3c-4d-5e`, and groups of four digits separated by dots, like
+//! `001a.2b3c.4d5e`.  The same separator must be used throughout.
+
+use std::convert::TryInto;
+use std::fmt::{self, Display};
+
+use super::hex_bytes::parse_hex_template;
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// A 48 bit MAC address.  Formatted as bytes separated by colons, using lower
+/// case digits.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+/// A 64 bit extended unique identifier.  Formatted as bytes separated by
+/// colons, using lower case digits.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Eui64(pub [u8; 8]);
+
+fn fmt_bytes(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            write!(f, ":")?;
+        }
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+impl Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_bytes(&self.0, f)
+    }
+}
+
+impl Display for Eui64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_bytes(&self.0, f)
+    }
+}
+
+/// Builds a template for [`parse_hex_template()`], for `len` bytes, using the
+/// separator that appears in `input`.  Unknown separators produce a template
+/// that fails at the position of the separator.
+fn template(input: &str, len: usize) -> String {
+    let first_separator = input
+        .bytes()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_hexdigit());
+
+    match first_separator {
+        Some((4, b'.')) => vec!["xxxx"; len / 2].join("."),
+        Some((2, sep)) if sep == b':' || sep == b'-' => {
+            vec!["xx"; len].join(&(sep as char).to_string())
+        }
+        _ => vec!["xx"; len].join(":"),
+    }
+}
+
+fn parse_hw_addr(input: &str, len: usize) -> Result<Vec<u8>, usize> {
+    parse_hex_template(input, &template(input, len))
+}
+
+/// Parses 48 bit MAC addresses.
+#[derive(PartialEq, Clone, Debug)]
+pub struct MacAddrArgParser {
+    name: Option<String>,
+}
+
+/// Accepts a MAC address in any of the supported forms.
+pub fn mac_addr() -> MacAddrArgParser {
+    MacAddrArgParser { name: None }
+}
+
+/// Names the argument.  Name will be included in the hint.
+pub fn mac_addr_with_name<Name>(name: Name) -> MacAddrArgParser
+where
+    Name: Into<String>,
+{
+    MacAddrArgParser {
+        name: Some(name.into()),
+    }
+}
+
+impl ContextFreeArgParser<MacAddr> for MacAddrArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<MacAddr> {
+        match parse_hw_addr(input, 6) {
+            Ok(bytes) => {
+                ArgParseRes::Parsed(MacAddr(bytes.try_into().unwrap()))
+            }
+            Err(parsed_up_to) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: self.hint(),
+            },
+        }
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        match &self.name {
+            Some(name) => vec![format!("<{}>", name)],
+            None => vec!["<mac address>".to_string()],
+        }
+    }
+}
+
+/// Parses 64 bit EUI-64 identifiers.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Eui64ArgParser {
+    name: Option<String>,
+}
+
+/// Accepts an EUI-64 in any of the supported forms.
+pub fn eui64() -> Eui64ArgParser {
+    Eui64ArgParser { name: None }
+}
+
+/// Names the argument.  Name will be included in the hint.
+pub fn eui64_with_name<Name>(name: Name) -> Eui64ArgParser
+where
+    Name: Into<String>,
+{
+    Eui64ArgParser {
+        name: Some(name.into()),
+    }
+}
+
+impl ContextFreeArgParser<Eui64> for Eui64ArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<Eui64> {
+        match parse_hw_addr(input, 8) {
+            Ok(bytes) => ArgParseRes::Parsed(Eui64(bytes.try_into().unwrap())),
+            Err(parsed_up_to) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: self.hint(),
+            },
+        }
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        match &self.name {
+            Some(name) => vec![format!("<{}>", name)],
+            None => vec!["<eui-64>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eui64, eui64_with_name, mac_addr, mac_addr_with_name};
+    use super::{Eui64, MacAddr};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    const MAC: MacAddr = MacAddr([0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]);
+
+    #[test]
+    fn mac() {
+        let parser = mac_addr();
+        let expected_hint = &["<mac address>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("00:1a:2b:3c:4d:5e", MAC);
+        check_parse("00-1A-2B-3C-4D-5E", MAC);
+        check_parse("001a.2b3c.4d5e", MAC);
+
+        check_failure("", 0, expected_hint);
+        check_failure("00:1a:2b", 8, expected_hint);
+        check_failure("00:1a:2g:3c:4d:5e", 7, expected_hint);
+        check_failure("00:1a-2b:3c:4d:5e", 5, expected_hint);
+        check_failure("00:1a:2b:3c:4d:5e:6f", 17, expected_hint);
+        check_failure("001a:2b3c:4d5e", 2, expected_hint);
+        check_failure("00_1a_2b_3c_4d_5e", 2, expected_hint);
+        check_failure("001a.2b3c4d5e", 9, expected_hint);
+        check_failure("001a2b3c4d5e", 2, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("00:", &[]);
+
+        assert_eq!(MAC.to_string(), "00:1a:2b:3c:4d:5e");
+    }
+
+    #[test]
+    fn eui() {
+        let parser = eui64();
+        let expected_hint = &["<eui-64>"];
+        let expected = Eui64([0x02, 0x1a, 0x2b, 0xff, 0xfe, 0x3c, 0x4d, 0x5e]);
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("02:1a:2b:ff:fe:3c:4d:5e", expected);
+        check_parse("02-1a-2b-ff-fe-3c-4d-5e", expected);
+        check_parse("021a.2bff.fe3c.4d5e", expected);
+
+        check_failure("02:1a:2b:ff:fe:3c", 17, expected_hint);
+        check_failure("021a.2bff.fe3c.4d5x", 18, expected_hint);
+
+        assert_eq!(expected.to_string(), "02:1a:2b:ff:fe:3c:4d:5e");
+    }
+
+    #[test]
+    fn named() {
+        let (check_hint, _, _, _) =
+            build_cf_parse_checkers("parser", mac_addr_with_name("nic"));
+        check_hint(&["<nic>"]);
+
+        let (check_hint, _, _, _) =
+            build_cf_parse_checkers("parser", eui64_with_name("node"));
+        check_hint(&["<node>"]);
+    }
+}
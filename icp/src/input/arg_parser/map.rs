@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{Arg2Parser, ArgParseRes, ContextFreeArgParser};
+use super::{Arg2Parser, ArgParseRes, ContextFreeArgParser, SuggestionDetails};
 
 use std::marker::PhantomData;
+use std::ops::Range;
 
 /// This parser runs another parser and applies a function to the value it
 /// produces, converting the return type.  Suggestions and hints are just passed
@@ -74,6 +75,210 @@ where
     fn hint(&self) -> Vec<String> {
         self.parser.hint()
     }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parser.suggestion_details(prefix)
+    }
+
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.parser.failure_span(input)
+    }
+
+    fn absent_value(&self) -> Option<B> {
+        self.parser.absent_value().map(&self.f)
+    }
+
+    fn consumes_rest(&self) -> bool {
+        self.parser.consumes_rest()
+    }
+}
+
+/// This parser runs another parser and applies a fallible function to the
+/// value it produces.  When the function returns an error, it is reported as a
+/// parse failure of the whole input.  Suggestions and hints are just passed as
+/// is.
+///
+/// You should use a `try_map` method on the parser, instead of using this type
+/// directly.
+pub struct ContextFreeTryMap<A, B, Parser, F>
+where
+    Parser: ContextFreeArgParser<A>,
+    F: Fn(A) -> Result<B, String>,
+{
+    parser: Parser,
+    f: F,
+    _a: PhantomData<fn(A)>,
+    _b: PhantomData<B>,
+}
+
+impl<A, B, Parser, F> ContextFreeTryMap<A, B, Parser, F>
+where
+    Parser: ContextFreeArgParser<A>,
+    F: Fn(A) -> Result<B, String>,
+{
+    pub fn new(parser: Parser, f: F) -> Self {
+        Self {
+            parser,
+            f,
+            _a: PhantomData,
+            _b: PhantomData,
+        }
+    }
+}
+
+impl<A, B, Parser, F> ContextFreeArgParser<B>
+    for ContextFreeTryMap<A, B, Parser, F>
+where
+    Parser: ContextFreeArgParser<A>,
+    F: Fn(A) -> Result<B, String>,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<B> {
+        match self.parser.parse(input) {
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            } => ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            },
+            ArgParseRes::Parsed(res) => match (self.f)(res) {
+                Ok(res) => ArgParseRes::Parsed(res),
+                // The input is well formed, but the value is not acceptable.
+                // Same as with out of range integers, the whole input is
+                // considered parsed.
+                Err(reason) => ArgParseRes::Failed {
+                    parsed_up_to: input.len(),
+                    reason: vec![reason],
+                },
+            },
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.parser.suggestion(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        self.parser.hint()
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parser.suggestion_details(prefix)
+    }
+
+    /// Values rejected by the function are at fault as a whole.
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        match self.parser.parse(input) {
+            ArgParseRes::Failed { .. } => self.parser.failure_span(input),
+            ArgParseRes::Parsed(_) => None,
+        }
+    }
+
+    /// An absent value that is rejected by the function makes the argument
+    /// required.  There is no input to report the rejection for, and no other
+    /// value to use instead.
+    fn absent_value(&self) -> Option<B> {
+        self.parser
+            .absent_value()
+            .and_then(|res| (self.f)(res).ok())
+    }
+
+    fn consumes_rest(&self) -> bool {
+        self.parser.consumes_rest()
+    }
+}
+
+/// This parser runs another parser and checks the value it produces with a
+/// predicate.  Values that do not satisfy the predicate are reported as parse
+/// failures, with a fixed message.
+///
+/// You should use a `validate` method on the parser, instead of using this
+/// type directly.
+pub struct ContextFreeValidate<Res, Parser, P>
+where
+    Parser: ContextFreeArgParser<Res>,
+    P: Fn(&Res) -> bool,
+{
+    parser: Parser,
+    pred: P,
+    message: String,
+    _res: PhantomData<fn() -> Res>,
+}
+
+impl<Res, Parser, P> ContextFreeValidate<Res, Parser, P>
+where
+    Parser: ContextFreeArgParser<Res>,
+    P: Fn(&Res) -> bool,
+{
+    pub fn new(parser: Parser, pred: P, message: String) -> Self {
+        Self {
+            parser,
+            pred,
+            message,
+            _res: PhantomData,
+        }
+    }
+}
+
+impl<Res, Parser, P> ContextFreeArgParser<Res>
+    for ContextFreeValidate<Res, Parser, P>
+where
+    Parser: ContextFreeArgParser<Res>,
+    P: Fn(&Res) -> bool,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<Res> {
+        match self.parser.parse(input) {
+            ArgParseRes::Parsed(res) if !(self.pred)(&res) => {
+                ArgParseRes::Failed {
+                    parsed_up_to: input.len(),
+                    reason: vec![self.message.clone()],
+                }
+            }
+            res => res,
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.parser.suggestion(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        self.parser.hint()
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parser.suggestion_details(prefix)
+    }
+
+    /// Values rejected by the predicate are at fault as a whole.
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        match self.parser.parse(input) {
+            ArgParseRes::Failed { .. } => self.parser.failure_span(input),
+            ArgParseRes::Parsed(_) => None,
+        }
+    }
+
+    /// Absent values are not checked, as they come from the code rather than
+    /// from the user.  Otherwise an [`optional()`] argument with a predicate
+    /// that rejects `None` would silently become required.
+    ///
+    /// [`optional()`]: super::optional::optional
+    fn absent_value(&self) -> Option<Res> {
+        self.parser.absent_value()
+    }
+
+    fn consumes_rest(&self) -> bool {
+        self.parser.consumes_rest()
+    }
 }
 
 /// Generates "context-sensitive" argument parser that maps another parser -
@@ -150,6 +355,34 @@ macro_rules! define_arg_parser_map {
             fn hint(&self, $( $arg_name: &$arg_type, )*) -> Vec<String> {
                 self.parser.hint($( $arg_name, )*)
             }
+
+            fn suggestion_details(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                prefix: &str,
+            ) -> Vec<(String, SuggestionDetails)> {
+                self.parser.suggestion_details($( $arg_name, )* prefix)
+            }
+
+            fn failure_span(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                input: &str,
+            ) -> Option<Range<usize>> {
+                self.parser.failure_span($( $arg_name, )* input)
+            }
+
+            fn absent_value(&self, $( $arg_name: &$arg_type, )*)
+                -> Option<$res2>
+            {
+                self.parser
+                    .absent_value($( $arg_name, )*)
+                    .map(|res| (self.f)($( $arg_name, )* res))
+            }
+
+            fn consumes_rest(&self, $( $arg_name: &$arg_type, )*) -> bool {
+                self.parser.consumes_rest($( $arg_name, )*)
+            }
         }
     }
 }
@@ -166,17 +399,272 @@ define_arg_parser_map!(
 //     Res3A, Res3B
 // );
 
+/// Generates "context-sensitive" argument parser that maps another parser with
+/// a fallible function - similar to [`ContextFreeTryMap`] but for
+/// [`Arg2Parser`] and friends.
+///
+/// You should use a `try_map` method on the parser, instead of using this type
+/// directly.
+macro_rules! define_arg_parser_try_map {
+    (
+        $name:ident: $parser_trait:ident,
+        { $( $arg_name:ident: $arg_type:ident ($phantom_name:ident) ),* $(,)* },
+        $res1:ident, $res2:ident
+    ) => {
+        pub struct $name<$( $arg_type, )* $res1, $res2, Parser, F>
+        where
+            Parser: $parser_trait<$( $arg_type, )* $res1>,
+            F: Fn($( &$arg_type, )* $res1) -> Result<$res2, String>,
+        {
+            parser: Parser,
+            f: F,
+            $( $phantom_name: PhantomData<$arg_type>, )*
+            _a: PhantomData<fn($res1)>,
+            _b: PhantomData<$res2>,
+        }
+
+        impl<$( $arg_type, )* $res1, $res2, Parser, F>
+            $name<$( $arg_type, )* $res1, $res2, Parser, F>
+        where
+            Parser: $parser_trait<$( $arg_type, )* $res1>,
+            F: Fn($( &$arg_type, )* $res1) -> Result<$res2, String>,
+        {
+            #[allow(unused)]
+            pub fn new(parser: Parser, f: F) -> Self
+            {
+                Self {
+                    parser,
+                    f,
+                    $( $phantom_name: PhantomData, )*
+                    _a: PhantomData,
+                    _b: PhantomData,
+                }
+            }
+        }
+
+        impl<$( $arg_type, )* $res1, $res2, Parser, F>
+            $parser_trait<$( $arg_type, )* $res2>
+            for $name<$( $arg_type, )* $res1, $res2, Parser, F>
+        where
+            Parser: $parser_trait<$( $arg_type, )* $res1>,
+            F: Fn($( &$arg_type, )* $res1) -> Result<$res2, String>,
+        {
+            fn parse(&self, $( $arg_name: &$arg_type, )* input: &str)
+                -> ArgParseRes<$res2>
+            {
+                match self.parser.parse($( $arg_name, )* input) {
+                    ArgParseRes::Failed { parsed_up_to, reason } =>
+                        ArgParseRes::Failed { parsed_up_to, reason },
+                    ArgParseRes::Parsed(res) => {
+                        match (self.f)($( $arg_name, )* res) {
+                            Ok(res) => ArgParseRes::Parsed(res),
+                            Err(reason) => ArgParseRes::Failed {
+                                parsed_up_to: input.len(),
+                                reason: vec![reason],
+                            },
+                        }
+                    }
+                }
+            }
+
+            fn suggestion(&self, $( $arg_name: &$arg_type, )* prefix: &str)
+                -> Vec<String>
+            {
+                self.parser.suggestion($( $arg_name, )* prefix)
+            }
+
+            fn hint(&self, $( $arg_name: &$arg_type, )*) -> Vec<String> {
+                self.parser.hint($( $arg_name, )*)
+            }
+
+            fn suggestion_details(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                prefix: &str,
+            ) -> Vec<(String, SuggestionDetails)> {
+                self.parser.suggestion_details($( $arg_name, )* prefix)
+            }
+
+            fn failure_span(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                input: &str,
+            ) -> Option<Range<usize>> {
+                match self.parser.parse($( $arg_name, )* input) {
+                    ArgParseRes::Failed { .. } => {
+                        self.parser.failure_span($( $arg_name, )* input)
+                    }
+                    ArgParseRes::Parsed(_) => None,
+                }
+            }
+
+            fn absent_value(&self, $( $arg_name: &$arg_type, )*)
+                -> Option<$res2>
+            {
+                // A rejected absent value makes the argument required.  See
+                // `ContextFreeTryMap::absent_value()`.
+                self.parser
+                    .absent_value($( $arg_name, )*)
+                    .and_then(|res| (self.f)($( $arg_name, )* res).ok())
+            }
+
+            fn consumes_rest(&self, $( $arg_name: &$arg_type, )*) -> bool {
+                self.parser.consumes_rest($( $arg_name, )*)
+            }
+        }
+    }
+}
+
+define_arg_parser_try_map!(
+    Arg2TryMap: Arg2Parser,
+    { res1: Res1 (_res1), },
+    Res2A, Res2B
+);
+
+// define_arg_parser_try_map!(
+//     Arg3TryMap: Arg3Parser,
+//     { res1: Res1 (_res1), res2: Res2 (_res2), },
+//     Res3A, Res3B
+// );
+
+/// Generates "context-sensitive" argument parser that validates values
+/// produced by another parser - similar to [`ContextFreeValidate`] but for
+/// [`Arg2Parser`] and friends.
+///
+/// You should use a `validate` method on the parser, instead of using this
+/// type directly.
+macro_rules! define_arg_parser_validate {
+    (
+        $name:ident: $parser_trait:ident,
+        { $( $arg_name:ident: $arg_type:ident ($phantom_name:ident) ),* $(,)* },
+        $res:ident
+    ) => {
+        pub struct $name<$( $arg_type, )* $res, Parser, P>
+        where
+            Parser: $parser_trait<$( $arg_type, )* $res>,
+            P: Fn($( &$arg_type, )* &$res) -> bool,
+        {
+            parser: Parser,
+            pred: P,
+            message: String,
+            $( $phantom_name: PhantomData<$arg_type>, )*
+            _res: PhantomData<fn() -> $res>,
+        }
+
+        impl<$( $arg_type, )* $res, Parser, P>
+            $name<$( $arg_type, )* $res, Parser, P>
+        where
+            Parser: $parser_trait<$( $arg_type, )* $res>,
+            P: Fn($( &$arg_type, )* &$res) -> bool,
+        {
+            #[allow(unused)]
+            pub fn new(parser: Parser, pred: P, message: String) -> Self
+            {
+                Self {
+                    parser,
+                    pred,
+                    message,
+                    $( $phantom_name: PhantomData, )*
+                    _res: PhantomData,
+                }
+            }
+        }
+
+        impl<$( $arg_type, )* $res, Parser, P>
+            $parser_trait<$( $arg_type, )* $res>
+            for $name<$( $arg_type, )* $res, Parser, P>
+        where
+            Parser: $parser_trait<$( $arg_type, )* $res>,
+            P: Fn($( &$arg_type, )* &$res) -> bool,
+        {
+            fn parse(&self, $( $arg_name: &$arg_type, )* input: &str)
+                -> ArgParseRes<$res>
+            {
+                match self.parser.parse($( $arg_name, )* input) {
+                    ArgParseRes::Parsed(res)
+                        if !(self.pred)($( $arg_name, )* &res) =>
+                    {
+                        ArgParseRes::Failed {
+                            parsed_up_to: input.len(),
+                            reason: vec![self.message.clone()],
+                        }
+                    }
+                    res => res,
+                }
+            }
+
+            fn suggestion(&self, $( $arg_name: &$arg_type, )* prefix: &str)
+                -> Vec<String>
+            {
+                self.parser.suggestion($( $arg_name, )* prefix)
+            }
+
+            fn hint(&self, $( $arg_name: &$arg_type, )*) -> Vec<String> {
+                self.parser.hint($( $arg_name, )*)
+            }
+
+            fn suggestion_details(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                prefix: &str,
+            ) -> Vec<(String, SuggestionDetails)> {
+                self.parser.suggestion_details($( $arg_name, )* prefix)
+            }
+
+            fn failure_span(
+                &self,
+                $( $arg_name: &$arg_type, )*
+                input: &str,
+            ) -> Option<Range<usize>> {
+                match self.parser.parse($( $arg_name, )* input) {
+                    ArgParseRes::Failed { .. } => {
+                        self.parser.failure_span($( $arg_name, )* input)
+                    }
+                    ArgParseRes::Parsed(_) => None,
+                }
+            }
+
+            fn absent_value(&self, $( $arg_name: &$arg_type, )*)
+                -> Option<$res>
+            {
+                // Absent values are not checked.  See
+                // `ContextFreeValidate::absent_value()`.
+                self.parser.absent_value($( $arg_name, )*)
+            }
+
+            fn consumes_rest(&self, $( $arg_name: &$arg_type, )*) -> bool {
+                self.parser.consumes_rest($( $arg_name, )*)
+            }
+        }
+    }
+}
+
+define_arg_parser_validate!(
+    Arg2Validate: Arg2Parser,
+    { res1: Res1 (_res1), },
+    Res2
+);
+
+// define_arg_parser_validate!(
+//     Arg3Validate: Arg3Parser,
+//     { res1: Res1 (_res1), res2: Res2 (_res2), },
+//     Res3
+// );
+
 #[cfg(test)]
 mod tests {
+    use std::convert::TryFrom;
     use std::string::ToString;
 
     use super::{Arg2Map, ContextFreeMap};
 
-    use crate::input::arg_parser::prim_int_for_range;
     use crate::input::arg_parser::test_utils::{
         build_arg2_parse_checkers, build_cf_parse_checkers,
     };
-    use crate::input::arg_parser::ContextFreeArgParser;
+    use crate::input::arg_parser::{
+        optional, prim_int_for_range, with_default,
+    };
+    use crate::input::arg_parser::{Arg2Parser, ContextFreeArgParser};
 
     #[test]
     fn simple_context_free_parser_adapter() {
@@ -279,4 +767,109 @@ mod tests {
         check_suggestions(&10, "0", &[]);
         check_suggestions(&7, "a", &[]);
     }
+
+    #[test]
+    fn context_free_try_map_and_validate() {
+        let parser = prim_int_for_range(0u16, 2000)
+            .validate(|v| v % 4 == 0, "must be 4-byte aligned")
+            .try_map(|v| u8::try_from(v / 4).map_err(|err| err.to_string()));
+
+        let expected_hint = &["<0-2000>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0", 0);
+        check_parse("128", 32);
+        check_parse("1020", 255);
+
+        check_failure("a", 0, expected_hint);
+        check_failure("2001", 4, &["max: 2000"]);
+        check_failure("130", 3, &["must be 4-byte aligned"]);
+        check_failure(
+            "1024",
+            4,
+            &["out of range integral type conversion attempted"],
+        );
+    }
+
+    #[test]
+    fn context_free_absent_value() {
+        let parser = optional(prim_int_for_range(0u16, 2000))
+            .validate(|v| v.is_some(), "must be present");
+        assert_eq!(parser.absent_value(), Some(None));
+
+        let parser = with_default(prim_int_for_range(0u16, 2000), 130)
+            .validate(|v| v % 4 == 0, "must be 4-byte aligned");
+        assert_eq!(parser.absent_value(), Some(130));
+
+        let to_u8 = |v: u16| u8::try_from(v).map_err(|err| err.to_string());
+        let parser =
+            with_default(prim_int_for_range(0u16, 2000), 200).try_map(to_u8);
+        assert_eq!(parser.absent_value(), Some(200));
+
+        let parser =
+            with_default(prim_int_for_range(0u16, 2000), 300).try_map(to_u8);
+        assert_eq!(parser.absent_value(), None);
+    }
+
+    #[test]
+    fn arg_2_try_map_and_validate() {
+        let parser = {
+            let int_parser = prim_int_for_range(0u8, 99).adapt();
+
+            // `ContextFreeAdapter` is also a `ContextFreeArgParser`, so the
+            // `Arg2Parser` methods need to be selected explicitly.
+            let validated = Arg2Parser::validate(
+                int_parser,
+                |limit: &u8, v: &u8| v <= limit,
+                "above the limit",
+            );
+
+            Arg2Parser::try_map(validated, |limit: &u8, v| {
+                if v == *limit {
+                    Err("equal to the limit".to_string())
+                } else {
+                    Ok(limit - v)
+                }
+            })
+        };
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_arg2_parse_checkers("parser", parser);
+
+        check_hint(&0, &["<0-99>"]);
+
+        check_parse(&10, "3", 7);
+        check_parse(&99, "0", 99);
+
+        check_failure(&10, "x", 0, &["<0-99>"]);
+        check_failure(&10, "11", 2, &["above the limit"]);
+        check_failure(&10, "10", 2, &["equal to the limit"]);
+    }
+
+    #[test]
+    fn arg_2_absent_value() {
+        let parser = Arg2Parser::validate(
+            optional(prim_int_for_range(0u8, 99)).adapt(),
+            |limit: &u8, v: &Option<u8>| v.is_some_and(|v| v <= *limit),
+            "above the limit",
+        );
+        assert_eq!(Arg2Parser::absent_value(&parser, &10), Some(None));
+
+        let parser = Arg2Parser::try_map(
+            with_default(prim_int_for_range(0u8, 99), 20).adapt(),
+            |limit: &u8, v: u8| {
+                if v <= *limit {
+                    Ok(limit - v)
+                } else {
+                    Err("above the limit".to_string())
+                }
+            },
+        );
+        assert_eq!(Arg2Parser::absent_value(&parser, &30), Some(10));
+        assert_eq!(Arg2Parser::absent_value(&parser, &10), None);
+    }
 }
@@ -0,0 +1,701 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsers for network addresses: IP addresses, CIDR networks, socket
+//! addresses and `host:port` pairs.
+//!
+//! Parsing is done manually, rather than via the `FromStr` implementations in
+//! `std::net`, in order to report the position of the offending part of the
+//! address, such as a bad octet or port.
+
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::string::ToString;
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// An IP network in CIDR notation, such as `10.0.0.0/8` or `fe80::/10`.
+///
+/// Host bits of the address are preserved as written, so `10.1.2.3/8` is
+/// accepted and keeps the `10.1.2.3` address.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct IpNet {
+    pub addr: IpAddr,
+    pub prefix_len: u8,
+}
+
+impl Display for IpNet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// A host name or an IP address, and a port, such as `device.local:8080`,
+/// `10.0.0.1:22` or `[::1]:22`.  Host names are not resolved.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct HostPort {
+    /// Host name or an IP address.  IPv6 addresses are stored without the
+    /// brackets.
+    pub host: String,
+    pub port: u16,
+}
+
+impl Display for HostPort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// Failure to parse an address.  Positions are relative to the whole argument.
+#[derive(PartialEq, Clone, Debug)]
+pub struct AddrError {
+    parsed_up_to: usize,
+    /// `None` means the parser hint should be used as the failure reason.
+    reason: Option<String>,
+}
+
+impl AddrError {
+    fn hint(parsed_up_to: usize) -> Self {
+        Self {
+            parsed_up_to,
+            reason: None,
+        }
+    }
+
+    fn new<Reason>(parsed_up_to: usize, reason: Reason) -> Self
+    where
+        Reason: Into<String>,
+    {
+        Self {
+            parsed_up_to,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Address types that can be parsed by the [`NetAddrArgParser`].
+pub trait NetAddr: Sized {
+    /// Hint used when the argument is not named.
+    const HINT: &'static str;
+
+    /// Parses `input`, that starts at `offset` in the whole argument.
+    fn parse_addr(input: &str, offset: usize) -> Result<Self, AddrError>;
+}
+
+fn parse_ipv4(input: &str, offset: usize) -> Result<Ipv4Addr, AddrError> {
+    let mut octets = [0u8; 4];
+    let mut count = 0;
+    let mut pos = 0;
+
+    for (i, octet) in input.split('.').enumerate() {
+        if i == octets.len() {
+            // Point at the extra dot.
+            return Err(AddrError::new(offset + pos - 1, "expected 4 octets"));
+        }
+
+        if let Some(j) = octet.find(|c: char| !c.is_ascii_digit()) {
+            return Err(AddrError::hint(offset + pos + j));
+        }
+        if octet.is_empty() {
+            return Err(AddrError::hint(offset + pos));
+        }
+        if octet.len() > 1 && octet.starts_with('0') {
+            return Err(AddrError::new(
+                offset + pos,
+                "octets may not have leading zeros",
+            ));
+        }
+
+        octets[i] = match u8::from_str(octet) {
+            Ok(v) => v,
+            Err(_) => {
+                return Err(AddrError::new(
+                    offset + pos,
+                    "octet must be within 0-255",
+                ))
+            }
+        };
+
+        count += 1;
+        pos += octet.len() + 1;
+    }
+
+    if count < octets.len() {
+        return Err(AddrError::hint(offset + input.len()));
+    }
+
+    Ok(Ipv4Addr::from(octets))
+}
+
+fn parse_ipv6(input: &str, offset: usize) -> Result<Ipv6Addr, AddrError> {
+    if let Some(i) =
+        input.find(|c: char| !(c.is_ascii_hexdigit() || c == ':' || c == '.'))
+    {
+        return Err(AddrError::hint(offset + i));
+    }
+
+    if let Some(i) = input.find(":::") {
+        return Err(AddrError::hint(offset + i + 2));
+    }
+    if let Some(first) = input.find("::") {
+        if let Some(second) = input[first + 1..].find("::") {
+            return Err(AddrError::new(
+                offset + first + 1 + second,
+                "'::' may only be used once",
+            ));
+        }
+    }
+
+    let mut pos = 0;
+    for group in input.split(':') {
+        // The last group may be an embedded IPv4 address, that is checked
+        // by `Ipv6Addr::from_str()` below.
+        if group.len() > 4 && !group.contains('.') {
+            return Err(AddrError::new(
+                offset + pos + 4,
+                "groups may have at most 4 hex digits",
+            ));
+        }
+        pos += group.len() + 1;
+    }
+
+    Ipv6Addr::from_str(input).map_err(|_| AddrError::hint(offset + input.len()))
+}
+
+fn parse_ip(input: &str, offset: usize) -> Result<IpAddr, AddrError> {
+    if input.contains(':') {
+        parse_ipv6(input, offset).map(IpAddr::V6)
+    } else {
+        parse_ipv4(input, offset).map(IpAddr::V4)
+    }
+}
+
+fn parse_port(input: &str, offset: usize) -> Result<u16, AddrError> {
+    if let Some(i) = input.find(|c: char| !c.is_ascii_digit()) {
+        return Err(AddrError::hint(offset + i));
+    }
+    if input.is_empty() {
+        return Err(AddrError::hint(offset));
+    }
+
+    u16::from_str(input)
+        .map_err(|_| AddrError::new(offset, "port must be within 0-65535"))
+}
+
+/// Part of an argument, together with its offset in the whole argument.
+type Part<'input> = (&'input str, usize);
+
+/// Splits `input` into a host and a port.  Host could be an IPv6 address in
+/// brackets, and is returned without the brackets.
+fn split_host_port(input: &str) -> Result<(Part<'_>, Part<'_>), AddrError> {
+    if let Some(bracketed) = input.strip_prefix('[') {
+        let close = match bracketed.find(']') {
+            Some(close) => close,
+            None => {
+                // Report any problems within the address first.
+                parse_ipv6(bracketed, 1)?;
+                return Err(AddrError::hint(input.len()));
+            }
+        };
+
+        let host = &bracketed[..close];
+        let after_host = close + 2;
+        return match input[after_host..].strip_prefix(':') {
+            Some(port) => Ok(((host, 1), (port, after_host + 1))),
+            None => Err(AddrError::hint(after_host)),
+        };
+    }
+
+    match input.find(':') {
+        Some(colon) => {
+            Ok(((&input[..colon], 0), (&input[colon + 1..], colon + 1)))
+        }
+        None => Err(AddrError::hint(input.len())),
+    }
+}
+
+fn check_host_name(input: &str) -> Result<(), AddrError> {
+    let mut pos = 0;
+
+    for label in input.split('.') {
+        if let Some(i) =
+            label.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        {
+            return Err(AddrError::hint(pos + i));
+        }
+        if label.is_empty() {
+            return Err(AddrError::hint(pos));
+        }
+        if label.starts_with('-') {
+            return Err(AddrError::new(
+                pos,
+                "host name parts may not start with '-'",
+            ));
+        }
+        if label.ends_with('-') {
+            return Err(AddrError::new(
+                pos + label.len() - 1,
+                "host name parts may not end with '-'",
+            ));
+        }
+        if label.len() > 63 {
+            return Err(AddrError::new(
+                pos + 63,
+                "host name parts may have at most 63 characters",
+            ));
+        }
+
+        pos += label.len() + 1;
+    }
+
+    Ok(())
+}
+
+impl NetAddr for IpAddr {
+    const HINT: &'static str = "<ip address>";
+
+    fn parse_addr(input: &str, offset: usize) -> Result<Self, AddrError> {
+        parse_ip(input, offset)
+    }
+}
+
+impl NetAddr for SocketAddr {
+    const HINT: &'static str = "<ip:port>";
+
+    fn parse_addr(input: &str, offset: usize) -> Result<Self, AddrError> {
+        let ((host, host_offset), (port, port_offset)) = split_host_port(input)
+            .map_err(|err| AddrError {
+                parsed_up_to: offset + err.parsed_up_to,
+                ..err
+            })?;
+
+        // IPv6 addresses must be in brackets, as they contain colons.
+        let ip = if host_offset == 0 {
+            IpAddr::V4(parse_ipv4(host, offset + host_offset)?)
+        } else {
+            IpAddr::V6(parse_ipv6(host, offset + host_offset)?)
+        };
+        let port = parse_port(port, offset + port_offset)?;
+
+        Ok(SocketAddr::new(ip, port))
+    }
+}
+
+impl NetAddr for IpNet {
+    const HINT: &'static str = "<ip/prefix length>";
+
+    fn parse_addr(input: &str, offset: usize) -> Result<Self, AddrError> {
+        let (addr, prefix_len) = match input.find('/') {
+            Some(slash) => (&input[..slash], &input[slash + 1..]),
+            None => {
+                parse_ip(input, offset)?;
+                return Err(AddrError::hint(offset + input.len()));
+            }
+        };
+
+        let addr = parse_ip(addr, offset)?;
+
+        let prefix_offset = offset + input.len() - prefix_len.len();
+        let max_prefix_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if let Some(i) = prefix_len.find(|c: char| !c.is_ascii_digit()) {
+            return Err(AddrError::hint(prefix_offset + i));
+        }
+        let prefix_len = match u8::from_str(prefix_len) {
+            Ok(v) if v <= max_prefix_len => v,
+            Ok(_) | Err(_) if !prefix_len.is_empty() => {
+                return Err(AddrError::new(
+                    prefix_offset,
+                    format!(
+                        "prefix length must be within 0-{}",
+                        max_prefix_len
+                    ),
+                ))
+            }
+            Ok(_) | Err(_) => return Err(AddrError::hint(prefix_offset)),
+        };
+
+        Ok(IpNet { addr, prefix_len })
+    }
+}
+
+impl NetAddr for HostPort {
+    const HINT: &'static str = "<host:port>";
+
+    fn parse_addr(input: &str, offset: usize) -> Result<Self, AddrError> {
+        let shift = |err: AddrError| AddrError {
+            parsed_up_to: offset + err.parsed_up_to,
+            ..err
+        };
+
+        let ((host, host_offset), (port, port_offset)) =
+            split_host_port(input).map_err(shift)?;
+
+        let looks_like_ipv4 =
+            host.chars().all(|c| c.is_ascii_digit() || c == '.');
+        if host_offset != 0 {
+            parse_ipv6(host, offset + host_offset)?;
+        } else if looks_like_ipv4 {
+            parse_ipv4(host, offset)?;
+        } else {
+            check_host_name(host).map_err(shift)?;
+        }
+
+        let port = parse_port(port, offset + port_offset)?;
+
+        Ok(HostPort {
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+/// Parses one of the network address types: [`IpAddr`], [`SocketAddr`],
+/// [`IpNet`] or [`HostPort`].
+///
+/// Optionally, suggests addresses from a list of known hosts.  The list is
+/// only used for suggestions, any valid address is accepted.
+#[derive(PartialEq, Clone, Debug)]
+pub struct NetAddrArgParser<T>
+where
+    T: NetAddr,
+{
+    known_hosts: Vec<String>,
+    name: Option<String>,
+    addr: PhantomData<T>,
+}
+
+impl<T> NetAddrArgParser<T>
+where
+    T: NetAddr,
+{
+    fn new(name: Option<String>) -> Self {
+        Self {
+            known_hosts: vec![],
+            name,
+            addr: PhantomData,
+        }
+    }
+
+    /// Suggests values from `hosts`, that start with the entered prefix.
+    /// Hosts should be written in the same form the parser accepts.
+    pub fn with_known_hosts<Host, Hosts>(mut self, hosts: Hosts) -> Self
+    where
+        Host: ToString,
+        Hosts: IntoIterator<Item = Host>,
+    {
+        self.known_hosts = hosts.into_iter().map(|h| h.to_string()).collect();
+        self
+    }
+}
+
+/// Accepts IPv4 and IPv6 addresses, such as `10.0.0.1` or `fe80::1`.
+pub fn ip_addr() -> NetAddrArgParser<IpAddr> {
+    NetAddrArgParser::new(None)
+}
+
+/// Same as [`ip_addr()`], but also names the argument.  Name will be included
+/// in the hint.
+pub fn ip_addr_with_name<Name>(name: Name) -> NetAddrArgParser<IpAddr>
+where
+    Name: Into<String>,
+{
+    NetAddrArgParser::new(Some(name.into()))
+}
+
+/// Accepts an IP address and a port, such as `10.0.0.1:80` or `[::1]:80`.
+pub fn socket_addr() -> NetAddrArgParser<SocketAddr> {
+    NetAddrArgParser::new(None)
+}
+
+/// Same as [`socket_addr()`], but also names the argument.  Name will be
+/// included in the hint.
+pub fn socket_addr_with_name<Name>(name: Name) -> NetAddrArgParser<SocketAddr>
+where
+    Name: Into<String>,
+{
+    NetAddrArgParser::new(Some(name.into()))
+}
+
+/// Accepts IP networks in CIDR notation, such as `10.0.0.0/8` or `fe80::/10`.
+pub fn ip_net() -> NetAddrArgParser<IpNet> {
+    NetAddrArgParser::new(None)
+}
+
+/// Same as [`ip_net()`], but also names the argument.  Name will be included
+/// in the hint.
+pub fn ip_net_with_name<Name>(name: Name) -> NetAddrArgParser<IpNet>
+where
+    Name: Into<String>,
+{
+    NetAddrArgParser::new(Some(name.into()))
+}
+
+/// Accepts a host name or an IP address and a port, such as
+/// `device.local:8080`.
+pub fn host_port() -> NetAddrArgParser<HostPort> {
+    NetAddrArgParser::new(None)
+}
+
+/// Same as [`host_port()`], but also names the argument.  Name will be
+/// included in the hint.
+pub fn host_port_with_name<Name>(name: Name) -> NetAddrArgParser<HostPort>
+where
+    Name: Into<String>,
+{
+    NetAddrArgParser::new(Some(name.into()))
+}
+
+impl<T> ContextFreeArgParser<T> for NetAddrArgParser<T>
+where
+    T: NetAddr,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<T> {
+        match T::parse_addr(input, 0) {
+            Ok(addr) => ArgParseRes::Parsed(addr),
+            Err(AddrError {
+                parsed_up_to,
+                reason,
+            }) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: match reason {
+                    Some(reason) => vec![reason],
+                    None => self.hint(),
+                },
+            },
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.known_hosts
+            .iter()
+            .filter(|h| h.starts_with(prefix) && h.len() > prefix.len())
+            .cloned()
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        match &self.name {
+            Some(name) => vec![format!("<{}>", name)],
+            None => vec![T::HINT.to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        host_port, host_port_with_name, ip_addr, ip_addr_with_name, ip_net,
+        socket_addr, HostPort, IpNet,
+    };
+
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    fn v4(a: u8, b: u8, c: u8, d: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+    }
+
+    #[test]
+    fn ip() {
+        let parser = ip_addr();
+        let expected_hint = &["<ip address>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("10.0.0.1", v4(10, 0, 0, 1));
+        check_parse("255.255.255.255", v4(255, 255, 255, 255));
+        check_parse("::1", IpAddr::V6(Ipv6Addr::LOCALHOST));
+        check_parse(
+            "fe80::1:2",
+            IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 1, 2)),
+        );
+        check_parse(
+            "::ffff:10.0.0.1",
+            IpAddr::V6(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped()),
+        );
+
+        check_failure("", 0, expected_hint);
+        check_failure("10.0", 4, expected_hint);
+        check_failure("10.0.0.", 7, expected_hint);
+        check_failure("10.0.256.1", 5, &["octet must be within 0-255"]);
+        check_failure("10.0.1000.1", 5, &["octet must be within 0-255"]);
+        check_failure("10.01.0.1", 3, &["octets may not have leading zeros"]);
+        check_failure("10.0.0.1.5", 8, &["expected 4 octets"]);
+        check_failure("10.0.x.1", 5, expected_hint);
+        check_failure("10..0.1", 3, expected_hint);
+        check_failure("fe80::g", 6, expected_hint);
+        check_failure("fe80:::1", 6, expected_hint);
+        check_failure("1::2::3", 4, &["'::' may only be used once"]);
+        check_failure("12345::1", 4, &["groups may have at most 4 hex digits"]);
+        check_failure("1:2:3", 5, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("10.", &[]);
+    }
+
+    #[test]
+    fn known_hosts() {
+        let parser = ip_addr_with_name("device").with_known_hosts([
+            "10.0.0.1",
+            "10.0.0.2",
+            "192.168.1.1",
+        ]);
+        let expected_hint = &["<device>"];
+
+        let (check_hint, check_suggestions, _check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_failure("10.0.0", 6, expected_hint);
+
+        check_suggestions("", &["10.0.0.1", "10.0.0.2", "192.168.1.1"]);
+        check_suggestions("10", &["10.0.0.1", "10.0.0.2"]);
+        check_suggestions("19", &["192.168.1.1"]);
+        check_suggestions("10.0.0.1", &[]);
+    }
+
+    #[test]
+    fn socket() {
+        let parser = socket_addr();
+        let expected_hint = &["<ip:port>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("10.0.0.1:80", SocketAddr::new(v4(10, 0, 0, 1), 80));
+        check_parse(
+            "[::1]:8080",
+            SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 8080),
+        );
+
+        check_failure("10.0.0.1", 8, expected_hint);
+        check_failure("10.0.0.1:", 9, expected_hint);
+        check_failure("10.0.0.1:8x", 10, expected_hint);
+        check_failure("10.0.0.1:65536", 9, &["port must be within 0-65535"]);
+        check_failure("10.0.300.1:80", 5, &["octet must be within 0-255"]);
+        check_failure("::1:80", 0, expected_hint);
+        check_failure("[::1", 4, expected_hint);
+        check_failure("[::g]:80", 3, expected_hint);
+        check_failure("[::1]80", 5, expected_hint);
+        check_failure("[::1]:x", 6, expected_hint);
+    }
+
+    #[test]
+    fn cidr() {
+        let parser = ip_net();
+        let expected_hint = &["<ip/prefix length>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse(
+            "10.0.0.0/8",
+            IpNet {
+                addr: v4(10, 0, 0, 0),
+                prefix_len: 8,
+            },
+        );
+        check_parse(
+            "fe80::/10",
+            IpNet {
+                addr: IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0)),
+                prefix_len: 10,
+            },
+        );
+
+        check_failure("10.0.0.0", 8, expected_hint);
+        check_failure("10.0.0/8", 6, expected_hint);
+        check_failure("10.0.0.0/", 9, expected_hint);
+        check_failure("10.0.0.0/8x", 10, expected_hint);
+        check_failure("10.0.0.0/33", 9, &["prefix length must be within 0-32"]);
+        check_failure("::/129", 3, &["prefix length must be within 0-128"]);
+        check_failure("::/1000", 3, &["prefix length must be within 0-128"]);
+
+        assert_eq!(
+            IpNet {
+                addr: v4(192, 168, 0, 0),
+                prefix_len: 16
+            }
+            .to_string(),
+            "192.168.0.0/16"
+        );
+    }
+
+    #[test]
+    fn host_and_port() {
+        let parser = host_port();
+        let expected_hint = &["<host:port>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        let host_port = |host: &str, port| HostPort {
+            host: host.to_string(),
+            port,
+        };
+
+        check_parse("device.local:8080", host_port("device.local", 8080));
+        check_parse("localhost:22", host_port("localhost", 22));
+        check_parse("10.0.0.1:22", host_port("10.0.0.1", 22));
+        check_parse("[fe80::1]:22", host_port("fe80::1", 22));
+
+        check_failure("device.local", 12, expected_hint);
+        check_failure("dev_ice:22", 3, expected_hint);
+        check_failure("device..local:22", 7, expected_hint);
+        check_failure(
+            "-device:22",
+            0,
+            &["host name parts may not start with '-'"],
+        );
+        check_failure(
+            "device-:22",
+            6,
+            &["host name parts may not end with '-'"],
+        );
+        check_failure("10.0.0.256:22", 7, &["octet must be within 0-255"]);
+        check_failure("device:99999", 7, &["port must be within 0-65535"]);
+
+        assert_eq!(host_port("fe80::1", 22).to_string(), "[fe80::1]:22");
+        assert_eq!(host_port("device", 22).to_string(), "device:22");
+
+        let (check_hint, check_suggestions, _, _) = build_cf_parse_checkers(
+            "parser",
+            host_port_with_name("target")
+                .with_known_hosts(vec!["board-a:22", "board-b:22"]),
+        );
+
+        check_hint(&["<target>"]);
+        check_suggestions("board-", &["board-a:22", "board-b:22"]);
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wrappers that make an argument optional.  Command parsers, such as
+//! [`crate::input::command_parser::command_2args()`], use
+//! [`ContextFreeArgParser::absent_value()`] when an argument is missing from
+//! the input, instead of reporting an error.
+//!
+//! As arguments are positional, only trailing arguments could be omitted.
+
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use super::{ArgParseRes, ContextFreeArgParser, SuggestionDetails};
+
+/// Hints of optional arguments are shown in square brackets: `<count>`
+/// becomes `[count]`.  Alternatives are joined inside a single pair of
+/// brackets, so `full` and `half` become `[full|half]`.
+fn optional_hint(hint: Vec<String>) -> Vec<String> {
+    if hint.is_empty() {
+        return hint;
+    }
+
+    let alternatives = hint
+        .iter()
+        .map(|h| {
+            h.strip_prefix('<')
+                .and_then(|h| h.strip_suffix('>'))
+                .unwrap_or(h)
+        })
+        .collect::<Vec<_>>();
+    vec![format!("[{}]", alternatives.join("|"))]
+}
+
+/// Produces `Some` with the value of the wrapped parser, or `None` when the
+/// argument is absent.  Constructed with [`optional()`].
+pub struct OptionalArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    parser: Parser,
+    _res: PhantomData<fn() -> Res>,
+}
+
+/// Makes the argument optional.  When absent, the argument value is `None`.
+pub fn optional<Parser, Res>(parser: Parser) -> OptionalArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    OptionalArgParser {
+        parser,
+        _res: PhantomData,
+    }
+}
+
+impl<Parser, Res> ContextFreeArgParser<Option<Res>>
+    for OptionalArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<Option<Res>> {
+        match self.parser.parse(input) {
+            ArgParseRes::Parsed(res) => ArgParseRes::Parsed(Some(res)),
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            } => ArgParseRes::Failed {
+                parsed_up_to,
+                reason,
+            },
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.parser.suggestion(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        optional_hint(self.parser.hint())
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parser.suggestion_details(prefix)
+    }
+
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.parser.failure_span(input)
+    }
+
+    fn absent_value(&self) -> Option<Option<Res>> {
+        Some(None)
+    }
+
+    fn consumes_rest(&self) -> bool {
+        self.parser.consumes_rest()
+    }
+}
+
+/// Produces the value of the wrapped parser, or a default value when the
+/// argument is absent.  Constructed with [`with_default()`].
+pub struct WithDefaultArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+    Res: Clone,
+{
+    parser: Parser,
+    default: Res,
+}
+
+/// Makes the argument optional.  When absent, the argument value is
+/// `default`.
+pub fn with_default<Parser, Res>(
+    parser: Parser,
+    default: Res,
+) -> WithDefaultArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+    Res: Clone,
+{
+    WithDefaultArgParser { parser, default }
+}
+
+impl<Parser, Res> ContextFreeArgParser<Res>
+    for WithDefaultArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+    Res: Clone,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<Res> {
+        self.parser.parse(input)
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.parser.suggestion(prefix)
+    }
+
+    fn hint(&self) -> Vec<String> {
+        optional_hint(self.parser.hint())
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parser.suggestion_details(prefix)
+    }
+
+    fn failure_span(&self, input: &str) -> Option<Range<usize>> {
+        self.parser.failure_span(input)
+    }
+
+    fn absent_value(&self) -> Option<Res> {
+        Some(self.default.clone())
+    }
+
+    fn consumes_rest(&self) -> bool {
+        self.parser.consumes_rest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{optional, with_default};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{
+        keyword_set, prim_int_with_name, ContextFreeArgParser,
+    };
+
+    #[test]
+    fn optional_value() {
+        let parser = optional(prim_int_with_name::<u8, _>("count"));
+
+        assert_eq!(parser.absent_value(), Some(None));
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["[count: 0-255]"]);
+
+        check_parse("7", Some(7));
+        check_failure("x", 0, &["<count: 0-255>"]);
+
+        check_suggestions("", &[]);
+    }
+
+    #[test]
+    fn default_value() {
+        let parser = with_default(keyword_set(["full", "half"]), "full".into());
+
+        assert_eq!(parser.absent_value(), Some("full".to_string()));
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["[full|half]"]);
+
+        check_parse("half", "half".to_string());
+        check_failure("hal", 3, &["full", "half"]);
+
+        check_suggestions("h", &["half"]);
+
+        let parser =
+            with_default(keyword_set(["a"]), "a".to_string()).map(|s| s.len());
+        assert_eq!(parser.absent_value(), Some(1));
+    }
+}
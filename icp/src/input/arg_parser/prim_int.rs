@@ -26,9 +26,9 @@ pub struct PrimIntArgParser<T>
 where
     T: PrimInt + FromStr + Display,
 {
-    min: T,
-    max: T,
-    name: Option<String>,
+    pub(super) min: T,
+    pub(super) max: T,
+    pub(super) name: Option<String>,
 }
 
 /// The allowed range of integers matches the range of values for the `T` type.
@@ -82,6 +82,39 @@ where
     }
 }
 
+impl<T> PrimIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    /// Checks `v` against the configured range.  `parsed_up_to` is used in the
+    /// failure, should the check fail.
+    pub(super) fn check_range(
+        &self,
+        v: T,
+        parsed_up_to: usize,
+    ) -> ArgParseRes<T> {
+        if v < self.min {
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason: match &self.name {
+                    Some(name) => vec![format!("min {}: {}", name, self.min)],
+                    None => vec![format!("min: {}", self.min)],
+                },
+            }
+        } else if v > self.max {
+            ArgParseRes::Failed {
+                parsed_up_to,
+                reason: match &self.name {
+                    Some(name) => vec![format!("max {}: {}", name, self.max)],
+                    None => vec![format!("max: {}", self.max)],
+                },
+            }
+        } else {
+            ArgParseRes::Parsed(v)
+        }
+    }
+}
+
 impl<T> ContextFreeArgParser<T> for PrimIntArgParser<T>
 where
     T: PrimInt + FromStr + Display,
@@ -106,31 +139,7 @@ where
         }
 
         match FromStr::from_str(input) {
-            Ok(v) => {
-                if v < self.min {
-                    ArgParseRes::Failed {
-                        parsed_up_to: input.len(),
-                        reason: match &self.name {
-                            Some(name) => {
-                                vec![format!("min {}: {}", name, self.min)]
-                            }
-                            None => vec![format!("min: {}", self.min)],
-                        },
-                    }
-                } else if v > self.max {
-                    ArgParseRes::Failed {
-                        parsed_up_to: input.len(),
-                        reason: match &self.name {
-                            Some(name) => {
-                                vec![format!("max {}: {}", name, self.max)]
-                            }
-                            None => vec![format!("max: {}", self.max)],
-                        },
-                    }
-                } else {
-                    ArgParseRes::Parsed(v)
-                }
-            }
+            Ok(v) => self.check_range(v, input.len()),
             Err(_) => {
                 // `FromStr` errors are very verbose and look strange in our
                 // context, so we just return out hint, hoping that the user
@@ -0,0 +1,419 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for physical quantities, such as `1.5MHz`, `64KiB` or `250ms`.
+
+use std::str::FromStr;
+
+use super::float::float_prefix_len;
+use super::{ArgParseRes, ContextFreeArgParser};
+
+use crate::input::common_prefix::common_prefix_len;
+
+/// A unit suffix and a factor that converts a value expressed in this unit
+/// into the base unit.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Unit {
+    pub suffix: String,
+    pub scale: f64,
+}
+
+/// Generates a unit table with SI prefixes from nano to giga, for the `base`
+/// unit.  For example, `si_units("Hz")` will contain `nHz`, `uHz`, `mHz`, `Hz`,
+/// `kHz`, `MHz` and `GHz`.
+pub fn si_units(base: &str) -> Vec<Unit> {
+    [
+        ("n", 1e-9),
+        ("u", 1e-6),
+        ("m", 1e-3),
+        ("", 1.0),
+        ("k", 1e3),
+        ("M", 1e6),
+        ("G", 1e9),
+    ]
+    .iter()
+    .map(|(prefix, scale)| Unit {
+        suffix: format!("{}{}", prefix, base),
+        scale: *scale,
+    })
+    .collect()
+}
+
+/// Generates a unit table with binary prefixes, as well as SI prefixes larger
+/// than one, for the `base` unit.  For example, `binary_units("B")` will
+/// contain `B`, `kB`, `KiB`, `MB`, `MiB`, `GB`, `GiB`, `TB` and `TiB`.
+pub fn binary_units(base: &str) -> Vec<Unit> {
+    [
+        ("", 1.0),
+        ("k", 1e3),
+        ("Ki", 1024.0),
+        ("M", 1e6),
+        ("Mi", 1024.0 * 1024.0),
+        ("G", 1e9),
+        ("Gi", 1024.0 * 1024.0 * 1024.0),
+        ("T", 1e12),
+        ("Ti", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+    ]
+    .iter()
+    .map(|(prefix, scale)| Unit {
+        suffix: format!("{}{}", prefix, base),
+        scale: *scale,
+    })
+    .collect()
+}
+
+/// Parses a number followed by a unit suffix from a unit table.  The value is
+/// normalized to the base unit, i.e. it is multiplied by the [`Unit::scale`].
+///
+/// Unit suffixes are case sensitive, as `m` and `M` are different prefixes.  A
+/// table may contain a unit with an empty suffix, in which case a bare number
+/// is accepted as well.
+#[derive(PartialEq, Clone, Debug)]
+pub struct QuantityArgParser {
+    units: Vec<Unit>,
+    min: Option<f64>,
+    max: Option<f64>,
+    name: Option<String>,
+}
+
+impl QuantityArgParser {
+    fn new(
+        units: Vec<Unit>,
+        min: Option<f64>,
+        max: Option<f64>,
+        name: Option<String>,
+    ) -> Self {
+        if units.is_empty() {
+            panic!("`units` should not be empty");
+        }
+
+        Self {
+            units,
+            min,
+            max,
+            name,
+        }
+    }
+}
+
+/// Accepts any finite value, in any of the `units`.
+pub fn quantity<Units>(units: Units) -> QuantityArgParser
+where
+    Units: IntoIterator<Item = Unit>,
+{
+    QuantityArgParser::new(units.into_iter().collect(), None, None, None)
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+/// Bounds are in the base units.
+pub fn quantity_for_range<Units>(
+    min: f64,
+    max: f64,
+    units: Units,
+) -> QuantityArgParser
+where
+    Units: IntoIterator<Item = Unit>,
+{
+    QuantityArgParser::new(
+        units.into_iter().collect(),
+        Some(min),
+        Some(max),
+        None,
+    )
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn quantity_with_name<Units, Name>(
+    units: Units,
+    name: Name,
+) -> QuantityArgParser
+where
+    Units: IntoIterator<Item = Unit>,
+    Name: Into<String>,
+{
+    QuantityArgParser::new(
+        units.into_iter().collect(),
+        None,
+        None,
+        Some(name.into()),
+    )
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+/// Bounds are in the base units.  Also names the argument.  Name will be
+/// included in the hint and in the error messages.
+pub fn quantity_for_range_and_name<Units, Name>(
+    min: f64,
+    max: f64,
+    units: Units,
+    name: Name,
+) -> QuantityArgParser
+where
+    Units: IntoIterator<Item = Unit>,
+    Name: Into<String>,
+{
+    QuantityArgParser::new(
+        units.into_iter().collect(),
+        Some(min),
+        Some(max),
+        Some(name.into()),
+    )
+}
+
+impl QuantityArgParser {
+    /// Suffix of the unit with a scale of 1, if there is one.
+    fn base_unit(&self) -> &str {
+        self.units
+            .iter()
+            .find(|unit| unit.scale == 1.0)
+            .map_or("", |unit| unit.suffix.as_str())
+    }
+
+    fn units_reason(&self) -> Vec<String> {
+        let suffixes = self
+            .units
+            .iter()
+            .map(|unit| unit.suffix.as_str())
+            .filter(|suffix| !suffix.is_empty())
+            .collect::<Vec<_>>();
+        vec![format!("units: {}", suffixes.join(", "))]
+    }
+
+    fn check_range(&self, v: f64, input: &str) -> ArgParseRes<f64> {
+        let (what, bound) = match (self.min, self.max) {
+            (Some(min), _) if v < min => ("min", min),
+            (_, Some(max)) if v > max => ("max", max),
+            _ => return ArgParseRes::Parsed(v),
+        };
+
+        ArgParseRes::Failed {
+            parsed_up_to: input.len(),
+            reason: match &self.name {
+                Some(name) => vec![format!(
+                    "{} {}: {}{}",
+                    what,
+                    name,
+                    bound,
+                    self.base_unit()
+                )],
+                None => {
+                    vec![format!("{}: {}{}", what, bound, self.base_unit())]
+                }
+            },
+        }
+    }
+}
+
+impl ContextFreeArgParser<f64> for QuantityArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<f64> {
+        let number_len = float_prefix_len(input);
+        if number_len == 0 {
+            return ArgParseRes::Failed {
+                parsed_up_to: 0,
+                reason: self.hint(),
+            };
+        }
+
+        let (number, suffix) = input.split_at(number_len);
+
+        let unit = match self.units.iter().find(|unit| unit.suffix == suffix) {
+            Some(unit) => unit,
+            None => {
+                let matched = self
+                    .units
+                    .iter()
+                    .map(|unit| common_prefix_len(suffix, &unit.suffix))
+                    .max()
+                    .unwrap_or(0);
+                return ArgParseRes::Failed {
+                    parsed_up_to: number_len + matched,
+                    reason: self.units_reason(),
+                };
+            }
+        };
+
+        match f64::from_str(number) {
+            Ok(v) if (v * unit.scale).is_finite() => {
+                self.check_range(v * unit.scale, input)
+            }
+            Ok(_) | Err(_) => ArgParseRes::Failed {
+                parsed_up_to: input.len(),
+                reason: self.hint(),
+            },
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        let number_len = float_prefix_len(prefix);
+        if number_len == 0 {
+            return vec![];
+        }
+
+        let (number, suffix) = prefix.split_at(number_len);
+        self.units
+            .iter()
+            .filter(|unit| {
+                unit.suffix.starts_with(suffix)
+                    && unit.suffix.len() > suffix.len()
+            })
+            .map(|unit| format!("{}{}", number, unit.suffix))
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let range = match (self.min, self.max) {
+            (Some(min), Some(max)) => {
+                Some(format!("{}-{}{}", min, max, self.base_unit()))
+            }
+            (Some(min), None) => Some(format!(">={}{}", min, self.base_unit())),
+            (None, Some(max)) => Some(format!("<={}{}", max, self.base_unit())),
+            (None, None) => None,
+        };
+
+        match (&self.name, range) {
+            (Some(name), Some(range)) => vec![format!("<{}: {}>", name, range)],
+            (Some(name), None) => vec![format!("<{}>", name)],
+            (None, Some(range)) => vec![format!("<{}>", range)],
+            (None, None) => {
+                let base = self.base_unit();
+                if base.is_empty() {
+                    vec!["<quantity>".to_string()]
+                } else {
+                    vec![format!("<quantity in {}>", base)]
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        binary_units, quantity, quantity_for_range_and_name,
+        quantity_with_name, si_units, Unit,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn frequency() {
+        let parser = quantity_with_name(si_units("Hz"), "freq");
+        let expected_hint = &["<freq>"];
+        let expected_units = &["units: nHz, uHz, mHz, Hz, kHz, MHz, GHz"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("1.5MHz", 1.5e6);
+        check_parse("100Hz", 100.0);
+        check_parse("2GHz", 2e9);
+        check_parse("250mHz", 0.25);
+
+        check_failure("", 0, expected_hint);
+        check_failure("MHz", 0, expected_hint);
+        check_failure("1", 1, expected_units);
+        check_failure("1.5M", 4, expected_units);
+        check_failure("1.5MHZ", 5, expected_units);
+        check_failure("1.5xHz", 3, expected_units);
+
+        check_suggestions("", &[]);
+        check_suggestions("k", &[]);
+        check_suggestions(
+            "1.5",
+            &[
+                "1.5nHz", "1.5uHz", "1.5mHz", "1.5Hz", "1.5kHz", "1.5MHz",
+                "1.5GHz",
+            ],
+        );
+        check_suggestions("1.5M", &["1.5MHz"]);
+        check_suggestions("1.5MH", &["1.5MHz"]);
+        check_suggestions("1.5MHz", &[]);
+        check_suggestions("1.5x", &[]);
+    }
+
+    #[test]
+    fn sizes_with_range() {
+        let parser = quantity_for_range_and_name(
+            1.0,
+            65536.0,
+            binary_units("B"),
+            "size",
+        );
+        let expected_hint = &["<size: 1-65536B>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("64KiB", 65536.0);
+        check_parse("64kB", 64000.0);
+        check_parse("512B", 512.0);
+        check_parse("0.5KiB", 512.0);
+
+        check_failure("0B", 2, &["min size: 1B"]);
+        check_failure("65KiB", 5, &["max size: 65536B"]);
+        check_failure("1MiB", 4, &["max size: 65536B"]);
+        check_failure(
+            "1Kb",
+            2,
+            &["units: B, kB, KiB, MB, MiB, GB, GiB, TB, TiB"],
+        );
+
+        check_suggestions("64K", &["64KiB"]);
+        check_suggestions("64G", &["64GB", "64GiB"]);
+    }
+
+    #[test]
+    fn optional_unit() {
+        let units = vec![
+            Unit {
+                suffix: "".to_string(),
+                scale: 1.0,
+            },
+            Unit {
+                suffix: "k".to_string(),
+                scale: 1e3,
+            },
+        ];
+        let parser = quantity(units);
+        let expected_hint = &["<quantity>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("3", 3.0);
+        check_parse("3k", 3000.0);
+
+        check_failure("3m", 1, &["units: k"]);
+        check_failure("1e400", 5, expected_hint);
+
+        check_suggestions("3", &["3k"]);
+    }
+
+    #[test]
+    fn unnamed_hint() {
+        let (check_hint, _, check_parse, _) =
+            build_cf_parse_checkers("parser", quantity(si_units("V")));
+
+        check_hint(&["<quantity in V>"]);
+        check_parse("3.3V", 3.3);
+        check_parse("-5mV", -0.005);
+    }
+}
@@ -0,0 +1,376 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Integer parser that understands `0x`, `0b` and `0o` prefixes, as well as
+//! `_` digit separators.  Similar to [`super::prim_int`], except that values
+//! in hints and error messages are shown in a configurable radix.
+
+use lazy_static::lazy_static;
+use num_traits::PrimInt;
+use regex::Regex;
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// Radix used to show values in hints and error messages.  Input is accepted
+/// in any radix, as long as it has an appropriate prefix.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Radix {
+    Bin,
+    Oct,
+    Dec,
+    Hex,
+}
+
+impl Radix {
+    /// Formats `v` in this radix, including the radix prefix.  Negative values
+    /// are shown as a minus sign followed by the magnitude, rather than in
+    /// two's complement.
+    pub fn format<T>(self, v: T) -> String
+    where
+        T: PrimInt,
+    {
+        let (sign, magnitude) = if v < T::zero() {
+            // Any signed primitive fits into `i128`.
+            ("-", v.to_i128().unwrap().unsigned_abs())
+        } else {
+            // Any non-negative primitive fits into `u128`.
+            ("", v.to_u128().unwrap())
+        };
+
+        match self {
+            Radix::Bin => format!("{}0b{:b}", sign, magnitude),
+            Radix::Oct => format!("{}0o{:o}", sign, magnitude),
+            Radix::Dec => format!("{}{}", sign, magnitude),
+            Radix::Hex => format!("{}0x{:x}", sign, magnitude),
+        }
+    }
+}
+
+lazy_static! {
+    static ref LITERAL_PREFIX: Regex = Regex::new(concat!(
+        r"^-?(",
+        r"0[xX][0-9a-fA-F][0-9a-fA-F_]*",
+        r"|0[bB][01][01_]*",
+        r"|0[oO][0-7][0-7_]*",
+        r"|[0-9][0-9_]*",
+        r")",
+    ))
+    .unwrap();
+}
+
+/// Returns the length of the longest prefix of `input` that forms an integer
+/// literal, or `0` if `input` does not start with a literal.
+pub(super) fn int_literal_prefix_len(input: &str) -> usize {
+    LITERAL_PREFIX.find(input).map_or(0, |m| m.end())
+}
+
+/// Parses `input` as an integer literal in any of the supported radixes.  On
+/// failure returns the `parsed_up_to` value, following the
+/// [`ArgParseRes::Failed`] conventions: end of the longest valid prefix for
+/// syntax errors, and `input.len()` for values that do not fit into `T`.
+pub(super) fn parse_int_literal<T>(input: &str) -> Result<T, usize>
+where
+    T: PrimInt,
+{
+    let literal_len = int_literal_prefix_len(input);
+    if literal_len != input.len() || literal_len == 0 {
+        return Err(literal_len);
+    }
+
+    let (negative, unsigned) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input),
+    };
+
+    let (radix, digits) = match unsigned.get(0..2) {
+        Some("0x") | Some("0X") => (16, &unsigned[2..]),
+        Some("0b") | Some("0B") => (2, &unsigned[2..]),
+        Some("0o") | Some("0O") => (8, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+
+    let mut normalized = String::with_capacity(input.len());
+    if negative {
+        normalized.push('-');
+    }
+    normalized.extend(digits.chars().filter(|c| *c != '_'));
+
+    T::from_str_radix(&normalized, radix).map_err(|_| input.len())
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    pub(super) min: T,
+    pub(super) max: T,
+    pub(super) radix: Radix,
+    pub(super) name: Option<String>,
+}
+
+/// The allowed range of integers matches the range of values for the `T` type.
+/// Values in hints are shown using `radix`.
+pub fn radix_int<T>(radix: Radix) -> RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    radix_int_for_range(T::min_value(), T::max_value(), radix)
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+pub fn radix_int_for_range<T>(
+    min: T,
+    max: T,
+    radix: Radix,
+) -> RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    RadixIntArgParser {
+        min,
+        max,
+        radix,
+        name: None,
+    }
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn radix_int_with_name<T, Name>(
+    radix: Radix,
+    name: Name,
+) -> RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: Into<String>,
+{
+    radix_int_for_range_and_name(T::min_value(), T::max_value(), radix, name)
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+/// Also names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn radix_int_for_range_and_name<T, Name>(
+    min: T,
+    max: T,
+    radix: Radix,
+    name: Name,
+) -> RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: Into<String>,
+{
+    RadixIntArgParser {
+        min,
+        max,
+        radix,
+        name: Some(name.into()),
+    }
+}
+
+impl<T> RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    /// Checks `v` against the configured range.  `parsed_up_to` is used in the
+    /// failure, should the check fail.
+    pub(super) fn check_range(
+        &self,
+        v: T,
+        parsed_up_to: usize,
+    ) -> ArgParseRes<T> {
+        let (label, bound) = if v < self.min {
+            ("min", self.min)
+        } else if v > self.max {
+            ("max", self.max)
+        } else {
+            return ArgParseRes::Parsed(v);
+        };
+
+        let bound = self.radix.format(bound);
+        ArgParseRes::Failed {
+            parsed_up_to,
+            reason: match &self.name {
+                Some(name) => vec![format!("{} {}: {}", label, name, bound)],
+                None => vec![format!("{}: {}", label, bound)],
+            },
+        }
+    }
+}
+
+impl<T> ContextFreeArgParser<T> for RadixIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<T> {
+        match parse_int_literal(input) {
+            Ok(v) => self.check_range(v, input.len()),
+            Err(parsed_up_to) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: self.hint(),
+            },
+        }
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let min = self.radix.format(self.min);
+        let max = self.radix.format(self.max);
+        let separator = if self.min < T::zero() { " - " } else { "-" };
+
+        match &self.name {
+            Some(name) => {
+                vec![format!("<{}: {}{}{}>", name, min, separator, max)]
+            }
+            None => vec![format!("<{}{}{}>", min, separator, max)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        int_literal_prefix_len, parse_int_literal, radix_int,
+        radix_int_for_range, radix_int_for_range_and_name, radix_int_with_name,
+        Radix,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn radix_format() {
+        assert_eq!(Radix::Hex.format(0u8), "0x0");
+        assert_eq!(Radix::Hex.format(255u8), "0xff");
+        assert_eq!(Radix::Hex.format(-128i8), "-0x80");
+        assert_eq!(Radix::Bin.format(5u32), "0b101");
+        assert_eq!(Radix::Oct.format(0o755u16), "0o755");
+        assert_eq!(Radix::Dec.format(i64::MIN), "-9223372036854775808");
+        assert_eq!(
+            Radix::Hex.format(u128::MAX),
+            "0xffffffffffffffffffffffffffffffff"
+        );
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(int_literal_prefix_len(""), 0);
+        assert_eq!(int_literal_prefix_len("-"), 0);
+        assert_eq!(int_literal_prefix_len("0x"), 1);
+        assert_eq!(int_literal_prefix_len("0x1_0z"), 5);
+        assert_eq!(int_literal_prefix_len("0b102"), 4);
+        assert_eq!(int_literal_prefix_len("-0o78"), 4);
+        assert_eq!(int_literal_prefix_len("12..34"), 2);
+
+        assert_eq!(parse_int_literal::<u32>("0x4000_1000"), Ok(0x4000_1000));
+        assert_eq!(parse_int_literal::<u32>("0X4000_1000"), Ok(0x4000_1000));
+        assert_eq!(parse_int_literal::<u8>("0b1010"), Ok(0b1010));
+        assert_eq!(parse_int_literal::<u16>("0o755"), Ok(0o755));
+        assert_eq!(parse_int_literal::<i16>("-0x10"), Ok(-16));
+        assert_eq!(parse_int_literal::<i32>("1_000_000"), Ok(1_000_000));
+        assert_eq!(parse_int_literal::<u8>("0x100"), Err(5));
+        assert_eq!(parse_int_literal::<u8>("-1"), Err(2));
+        assert_eq!(parse_int_literal::<u8>("0xg"), Err(1));
+        assert_eq!(parse_int_literal::<u8>("_1"), Err(0));
+    }
+
+    #[test]
+    fn u8_hex() {
+        let parser = radix_int::<u8>(Radix::Hex);
+        let expected_hint = &["<0x0-0xff>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("0", 0);
+        check_parse("255", 255);
+        check_parse("0xff", 255);
+        check_parse("0xF_F", 255);
+        check_parse("0b1010_1010", 0xaa);
+        check_parse("0o17", 15);
+
+        check_failure("", 0, expected_hint);
+        check_failure("x", 0, expected_hint);
+        check_failure("0x", 1, expected_hint);
+        check_failure("0xfg", 3, expected_hint);
+        check_failure("0b2", 1, expected_hint);
+        check_failure("0o8", 1, expected_hint);
+        check_failure("0x100", 5, expected_hint);
+        check_failure("-1", 2, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("0x", &[]);
+    }
+
+    #[test]
+    fn i16_with_range_and_name() {
+        let parser =
+            radix_int_for_range_and_name(-0x80i16, 0x7f, Radix::Hex, "offset");
+        let expected_below_hint = &["min offset: -0x80"];
+        let expected_above_hint = &["max offset: 0x7f"];
+        let expected_hint = &["<offset: -0x80 - 0x7f>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("-0x80", -128);
+        check_parse("-128", -128);
+        check_parse("0x7f", 127);
+        check_parse("-0b1", -1);
+
+        check_failure("-0x81", 5, expected_below_hint);
+        check_failure("0x80", 4, expected_above_hint);
+        check_failure("0x7_f0", 6, expected_above_hint);
+        check_failure("0x7-", 3, expected_hint);
+        check_failure("--1", 0, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("-0x", &[]);
+    }
+
+    #[test]
+    fn u16_named_and_ranged() {
+        let (check_hint, _, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "named",
+                radix_int_with_name::<u16, _>(Radix::Hex, "addr"),
+            );
+
+        check_hint(&["<addr: 0x0-0xffff>"]);
+        check_parse("0x4000", 0x4000);
+        check_failure("0x1_0000", 8, &["<addr: 0x0-0xffff>"]);
+
+        let (check_hint, _, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "ranged",
+                radix_int_for_range(0o10u16, 0o777, Radix::Oct),
+            );
+
+        check_hint(&["<0o10-0o777>"]);
+        check_parse("0o755", 0o755);
+        check_parse("8", 8);
+        check_failure("7", 1, &["min: 0o10"]);
+        check_failure("0b1_0000_0000_0", 15, &["max: 0o777"]);
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+use regex_automata::dfa::{dense, Automaton};
+use regex_automata::{Anchored, Input};
+
+use super::{ArgParseRes, ContextFreeArgParser};
+
+/// Accepts any string that matches a regular expression, returning it as is.
+/// Useful for identifiers and labels, that are not limited to a fixed list of
+/// values.
+///
+/// The pattern is anchored at both ends, so the whole argument must match.
+/// When it does not, `parsed_up_to` is the length of the longest prefix of the
+/// argument that could still be extended into a match.
+#[derive(Clone, Debug)]
+pub struct RegexStringArgParser {
+    pattern: String,
+    regex: Regex,
+    /// Used to find how far the input matches the pattern.  `None` for
+    /// patterns that a DFA can not represent, like ones with Unicode word
+    /// boundaries.
+    dfa: Option<dense::DFA<Vec<u32>>>,
+    min_len: usize,
+    max_len: Option<usize>,
+    name: Option<String>,
+}
+
+impl RegexStringArgParser {
+    fn new(pattern: &str, name: Option<String>) -> Self {
+        let anchored = format!("^(?:{})$", pattern);
+        let regex = match Regex::new(&anchored) {
+            Ok(regex) => regex,
+            Err(err) => panic!("`pattern` is not a valid regex: {}", err),
+        };
+        let dfa = dense::DFA::new(&anchored).ok();
+
+        Self {
+            pattern: pattern.to_string(),
+            regex,
+            dfa,
+            min_len: 0,
+            max_len: None,
+            name,
+        }
+    }
+
+    /// Restricts the length of the argument to be within `[min, max]`
+    /// characters - both ends are included.  `None` for `max` means there is
+    /// no upper limit.
+    pub fn with_len_limits(mut self, min: usize, max: Option<usize>) -> Self {
+        self.min_len = min;
+        self.max_len = max;
+        self
+    }
+}
+
+/// `Regex` does not implement `PartialEq`, so parsers are compared using the
+/// source of the pattern.
+impl PartialEq for RegexStringArgParser {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && self.min_len == other.min_len
+            && self.max_len == other.max_len
+            && self.name == other.name
+    }
+}
+
+/// Accepts strings that fully match `pattern`.  Panics if `pattern` is not a
+/// valid regular expression.
+pub fn string_matching(pattern: &str) -> RegexStringArgParser {
+    RegexStringArgParser::new(pattern, None)
+}
+
+/// Accepts strings that fully match `pattern`.  Also names the argument.  Name
+/// will be included in the hint and in the error messages.
+pub fn string_matching_with_name<Name>(
+    pattern: &str,
+    name: Name,
+) -> RegexStringArgParser
+where
+    Name: Into<String>,
+{
+    RegexStringArgParser::new(pattern, Some(name.into()))
+}
+
+impl RegexStringArgParser {
+    /// Runs the DFA over the input until it reaches the dead state - the
+    /// point after which no continuation could match the pattern.  The
+    /// character that caused the transition is the first one that is wrong.
+    fn matched_prefix_len(&self, input: &str) -> usize {
+        let dfa = match &self.dfa {
+            Some(dfa) => dfa,
+            None => return 0,
+        };
+
+        let start = Input::new(input).anchored(Anchored::Yes);
+        let mut state = match dfa.start_state_forward(&start) {
+            Ok(state) => state,
+            Err(_) => return 0,
+        };
+
+        for (i, c) in input.char_indices() {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                state = dfa.next_state(state, b);
+                if dfa.is_dead_state(state) || dfa.is_quit_state(state) {
+                    return i;
+                }
+            }
+        }
+
+        input.len()
+    }
+
+    fn check_len(&self, input: &str) -> ArgParseRes<String> {
+        let chars_str = |len: usize| {
+            if len == 1 {
+                "1 character".to_string()
+            } else {
+                format!("{} characters", len)
+            }
+        };
+        let named = |what: &str| match &self.name {
+            Some(name) => format!("{} {}", what, name),
+            None => what.to_string(),
+        };
+
+        let len = input.chars().count();
+        let reason = if len < self.min_len {
+            format!("{}: {}", named("min length"), chars_str(self.min_len))
+        } else {
+            match self.max_len {
+                Some(max_len) if len > max_len => {
+                    format!("{}: {}", named("max length"), chars_str(max_len))
+                }
+                _ => return ArgParseRes::Parsed(input.to_string()),
+            }
+        };
+
+        ArgParseRes::Failed {
+            parsed_up_to: input.len(),
+            reason: vec![reason],
+        }
+    }
+}
+
+impl ContextFreeArgParser<String> for RegexStringArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<String> {
+        if !self.regex.is_match(input) {
+            return ArgParseRes::Failed {
+                parsed_up_to: self.matched_prefix_len(input),
+                reason: self.hint(),
+            };
+        }
+
+        self.check_len(input)
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let len = match (self.min_len, self.max_len) {
+            (0, None) => None,
+            (min, None) => Some(format!(">={} chars", min)),
+            (min, Some(max)) => Some(format!("{}-{} chars", min, max)),
+        };
+
+        match (&self.name, len) {
+            (Some(name), Some(len)) => vec![format!("<{}: {}>", name, len)],
+            (Some(name), None) => vec![format!("<{}>", name)],
+            (None, Some(len)) => {
+                vec![format!("<string matching {}: {}>", self.pattern, len)]
+            }
+            (None, None) => vec![format!("<string matching {}>", self.pattern)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{string_matching, string_matching_with_name};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    #[test]
+    fn identifier() {
+        let parser = string_matching_with_name("[a-z_][a-z0-9_]*", "label");
+        let expected_hint = &["<label>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("loop", "loop".to_string());
+        check_parse("_start2", "_start2".to_string());
+
+        check_failure("", 0, expected_hint);
+        check_failure("2nd", 0, expected_hint);
+        check_failure("ab-cd", 2, expected_hint);
+        check_failure("abC", 2, expected_hint);
+        check_failure("ab cd", 2, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("ab", &[]);
+    }
+
+    #[test]
+    fn anchored() {
+        let parser = string_matching("ab|cd");
+        let expected_hint = &["<string matching ab|cd>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("ab", "ab".to_string());
+        check_parse("cd", "cd".to_string());
+
+        check_failure("abcd", 2, expected_hint);
+        check_failure("xab", 0, expected_hint);
+        check_failure("é", 0, expected_hint);
+    }
+
+    #[test]
+    fn partial_match() {
+        let parser = string_matching_with_name("[a-z]{3}[0-9]{3}", "id");
+        let expected_hint = &["<id>"];
+
+        let (_, _, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("abc123", "abc123".to_string());
+
+        check_failure("abc12x", 5, expected_hint);
+        check_failure("ab1", 2, expected_hint);
+        check_failure("abc12", 5, expected_hint);
+        check_failure("abc1234", 6, expected_hint);
+        check_failure("abcé", 3, expected_hint);
+    }
+
+    #[test]
+    fn length_limits() {
+        let parser = string_matching_with_name(r"\w+", "tag")
+            .with_len_limits(2, Some(4));
+        let expected_hint = &["<tag: 2-4 chars>"];
+
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("ab", "ab".to_string());
+        check_parse("äbcd", "äbcd".to_string());
+
+        check_failure("a", 1, &["min length tag: 2 characters"]);
+        check_failure("abcde", 5, &["max length tag: 4 characters"]);
+        check_failure("ab!", 2, expected_hint);
+
+        let (check_hint, _, _, check_failure) = build_cf_parse_checkers(
+            "parser",
+            string_matching("[0-9]+").with_len_limits(1, None),
+        );
+
+        check_hint(&["<string matching [0-9]+: >=1 chars>"]);
+        check_failure("", 0, &["<string matching [0-9]+: >=1 chars>"]);
+    }
+}
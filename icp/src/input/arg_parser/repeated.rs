@@ -0,0 +1,313 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Arguments that could be repeated, like file names in `load file1 file2`.
+//! These parsers [consume the rest] of the command input, so they need to be
+//! the last argument of a command.
+//!
+//! [consume the rest]: ContextFreeArgParser::consumes_rest
+
+use std::marker::PhantomData;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{
+    prefix_suggestion_details, ArgParseRes, ContextFreeArgParser,
+    SuggestionDetails,
+};
+
+/// Parses every word of the input with the same parser, producing a vector of
+/// values.  Constructed with [`many()`], [`at_least()`] or [`between()`].
+pub struct RepeatedArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    parser: Parser,
+    min: usize,
+    max: Option<usize>,
+    _res: PhantomData<fn() -> Res>,
+}
+
+/// Any number of values, including none.
+pub fn many<Parser, Res>(parser: Parser) -> RepeatedArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    RepeatedArgParser::new(parser, 0, None)
+}
+
+/// `min` or more values.
+pub fn at_least<Parser, Res>(
+    parser: Parser,
+    min: usize,
+) -> RepeatedArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    RepeatedArgParser::new(parser, min, None)
+}
+
+/// From `min` to `max` values, inclusive.
+pub fn between<Parser, Res>(
+    parser: Parser,
+    min: usize,
+    max: usize,
+) -> RepeatedArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    RepeatedArgParser::new(parser, min, Some(max))
+}
+
+impl<Parser, Res> RepeatedArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    fn new(parser: Parser, min: usize, max: Option<usize>) -> Self {
+        if let Some(max) = max {
+            if max == 0 || min > max {
+                panic!(
+                    "`max` should be positive and not less than `min`.\n\
+                     min: {}\n\
+                     max: {}",
+                    min, max
+                );
+            }
+        }
+
+        Self {
+            parser,
+            min,
+            max,
+            _res: PhantomData,
+        }
+    }
+
+    /// Splits `prefix` into the complete words, with any whitespace after
+    /// them, and the word that `prefix` ends with.  The latter is an empty
+    /// string, if `prefix` ends with a space.  `None` if there is no place for
+    /// another value.
+    fn split_current_word<'a>(
+        &self,
+        prefix: &'a str,
+    ) -> Option<(&'a str, &'a str)> {
+        let mut words = WORD.find_iter(prefix).collect::<Vec<_>>();
+
+        let current_start = match words.last() {
+            Some(last) if last.end() == prefix.len() => {
+                words.pop().unwrap().start()
+            }
+            _ => prefix.len(),
+        };
+
+        if self.max.is_some_and(|max| words.len() >= max) {
+            return None;
+        }
+
+        Some(prefix.split_at(current_start))
+    }
+}
+
+/// `1 value` or `2 values`.
+fn values(count: usize) -> String {
+    if count == 1 {
+        "1 value".to_string()
+    } else {
+        format!("{} values", count)
+    }
+}
+
+lazy_static! {
+    static ref WORD: Regex = Regex::new(r"\S+").unwrap();
+}
+
+impl<Parser, Res> ContextFreeArgParser<Vec<Res>>
+    for RepeatedArgParser<Parser, Res>
+where
+    Parser: ContextFreeArgParser<Res>,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<Vec<Res>> {
+        let mut res = vec![];
+        let mut prev_end = 0;
+
+        for m in WORD.find_iter(input) {
+            if self.max == Some(res.len()) {
+                return ArgParseRes::Failed {
+                    parsed_up_to: prev_end,
+                    reason: vec![format!(
+                        "expected at most {}",
+                        values(res.len())
+                    )],
+                };
+            }
+
+            match self.parser.parse(m.as_str()) {
+                ArgParseRes::Parsed(value) => res.push(value),
+                ArgParseRes::Failed {
+                    parsed_up_to,
+                    reason,
+                } => {
+                    return ArgParseRes::Failed {
+                        parsed_up_to: if parsed_up_to > 0 {
+                            m.start() + parsed_up_to
+                        } else {
+                            prev_end
+                        },
+                        reason,
+                    }
+                }
+            }
+
+            prev_end = m.end();
+        }
+
+        if res.len() < self.min {
+            return ArgParseRes::Failed {
+                parsed_up_to: prev_end,
+                reason: vec![format!("expected at least {}", values(self.min))],
+            };
+        }
+
+        ArgParseRes::Parsed(res)
+    }
+
+    /// Suggestions are for the word that `prefix` ends with, or for the next
+    /// value, if `prefix` ends with a space.  They follow all the preceding
+    /// words.
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        match self.split_current_word(prefix) {
+            Some((before, current)) => self
+                .parser
+                .suggestion(current)
+                .into_iter()
+                .map(|suggestion| format!("{}{}", before, suggestion))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        match self.split_current_word(prefix) {
+            Some((before, current)) => prefix_suggestion_details(
+                before,
+                self.parser.suggestion_details(current),
+            ),
+            None => vec![],
+        }
+    }
+
+    /// `<file>` becomes `<file>...`.
+    fn hint(&self) -> Vec<String> {
+        self.parser
+            .hint()
+            .into_iter()
+            .map(|h| format!("{}...", h))
+            .collect()
+    }
+
+    fn absent_value(&self) -> Option<Vec<Res>> {
+        if self.min == 0 {
+            Some(vec![])
+        } else {
+            None
+        }
+    }
+
+    fn consumes_rest(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{at_least, between, many};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{
+        keyword_set, prim_int_with_name, ContextFreeArgParser,
+    };
+
+    #[test]
+    fn many_values() {
+        let parser = many(prim_int_with_name::<u8, _>("size"));
+
+        assert_eq!(parser.absent_value(), Some(vec![]));
+        assert!(parser.consumes_rest());
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(&["<size: 0-255>..."]);
+
+        check_parse("", vec![]);
+        check_parse("7", vec![7]);
+        check_parse("1 2  3", vec![1, 2, 3]);
+
+        check_failure("1 x 3", 1, &["<size: 0-255>"]);
+        check_failure("1 23x", 4, &["<size: 0-255>"]);
+
+        check_suggestions("1 ", &[]);
+    }
+
+    #[test]
+    fn bounds() {
+        let (check_hint, _check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                between(prim_int_with_name::<u8, _>("size"), 1, 2),
+            );
+
+        check_hint(&["<size: 0-255>..."]);
+
+        check_parse("1", vec![1]);
+        check_parse("1 2", vec![1, 2]);
+
+        check_failure("", 0, &["expected at least 1 value"]);
+        check_failure("1 2 3", 3, &["expected at most 2 values"]);
+
+        let parser = at_least(prim_int_with_name::<u8, _>("size"), 2);
+        assert_eq!(parser.absent_value(), None);
+
+        let (_, _, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_parse("1 2 3 4", vec![1, 2, 3, 4]);
+        check_failure("1", 1, &["expected at least 2 values"]);
+    }
+
+    #[test]
+    fn suggestions() {
+        let (_, check_suggestions, _, _) = build_cf_parse_checkers(
+            "parser",
+            between(keyword_set(["full", "half"]), 1, 2),
+        );
+
+        check_suggestions("", &["full", "half"]);
+        check_suggestions("h", &["half"]);
+        check_suggestions("full ", &["full full", "full half"]);
+        check_suggestions("full f", &["full full"]);
+        check_suggestions("half  h", &["half  half"]);
+        check_suggestions("full half ", &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`max` should be positive")]
+    fn invalid_bounds() {
+        between(keyword_set(["a"]), 2, 1);
+    }
+}
@@ -0,0 +1,348 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_traits::PrimInt;
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::string::ToString;
+
+use super::prim_int::PrimIntArgParser;
+use super::{ArgParseRes, ContextFreeArgParser, SuggestionDetails};
+
+use crate::input::common_prefix::common_prefix_len;
+
+/// Parses integers, same as [`PrimIntArgParser`], but also accepts names from
+/// a table of constants, such as `UART0_BASE` or `MAX`.  Names are resolved
+/// into their values, and the values are subject to the same range checks as
+/// the numbers.
+///
+/// Names are suggested while typing, with values provided as suggestion
+/// descriptions, so frontends could show them alongside, like
+/// `UART0_BASE  4096`.  Constants that are outside of the allowed range are not
+/// suggested.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    number: PrimIntArgParser<T>,
+    constants: Vec<(String, T)>,
+}
+
+impl<T> SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn new(
+        min: T,
+        max: T,
+        name: Option<String>,
+        constants: Vec<(String, T)>,
+    ) -> Self {
+        if constants.is_empty() {
+            panic!("`constants` should not be empty");
+        }
+
+        Self {
+            number: PrimIntArgParser { min, max, name },
+            constants,
+        }
+    }
+}
+
+fn collect_constants<T, Name, Constants>(
+    constants: Constants,
+) -> Vec<(String, T)>
+where
+    Name: ToString,
+    Constants: IntoIterator<Item = (Name, T)>,
+{
+    constants
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+/// The allowed range of integers matches the range of values for the `T` type.
+pub fn symbolic_int<T, Name, Constants>(
+    constants: Constants,
+) -> SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: ToString,
+    Constants: IntoIterator<Item = (Name, T)>,
+{
+    SymbolicIntArgParser::new(
+        T::min_value(),
+        T::max_value(),
+        None,
+        collect_constants(constants),
+    )
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+pub fn symbolic_int_for_range<T, Name, Constants>(
+    min: T,
+    max: T,
+    constants: Constants,
+) -> SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: ToString,
+    Constants: IntoIterator<Item = (Name, T)>,
+{
+    SymbolicIntArgParser::new(min, max, None, collect_constants(constants))
+}
+
+/// Names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn symbolic_int_with_name<T, Name, Constants, ArgName>(
+    constants: Constants,
+    name: ArgName,
+) -> SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: ToString,
+    Constants: IntoIterator<Item = (Name, T)>,
+    ArgName: Into<String>,
+{
+    SymbolicIntArgParser::new(
+        T::min_value(),
+        T::max_value(),
+        Some(name.into()),
+        collect_constants(constants),
+    )
+}
+
+/// Restricts the allowed range to be `[min, max]` - both end are included.
+/// Also names the argument.  Name will be included in the hint and in the error
+/// messages.
+pub fn symbolic_int_for_range_and_name<T, Name, Constants, ArgName>(
+    min: T,
+    max: T,
+    constants: Constants,
+    name: ArgName,
+) -> SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+    Name: ToString,
+    Constants: IntoIterator<Item = (Name, T)>,
+    ArgName: Into<String>,
+{
+    SymbolicIntArgParser::new(
+        min,
+        max,
+        Some(name.into()),
+        collect_constants(constants),
+    )
+}
+
+impl<T> SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn in_range(&self, v: T) -> bool {
+        self.number.min <= v && v <= self.number.max
+    }
+
+    /// Constants with names that extend `prefix`.
+    fn suggested<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = &'a (String, T)> {
+        self.constants.iter().filter(move |(name, value)| {
+            name.starts_with(prefix)
+                && name.len() > prefix.len()
+                && self.in_range(*value)
+        })
+    }
+}
+
+impl<T> ContextFreeArgParser<T> for SymbolicIntArgParser<T>
+where
+    T: PrimInt + FromStr + Display,
+{
+    fn parse(&self, input: &str) -> ArgParseRes<T> {
+        if let Some((_, value)) =
+            self.constants.iter().find(|(name, _)| name == input)
+        {
+            return self.number.check_range(*value, input.len());
+        }
+
+        // Numbers that parsed completely, but failed the range check, produce
+        // more helpful failures than a generic hint, so those are preserved.
+        let number_parsed_up_to = match self.number.parse(input) {
+            ArgParseRes::Failed { parsed_up_to, .. }
+                if parsed_up_to < input.len() || input.is_empty() =>
+            {
+                parsed_up_to
+            }
+            res => return res,
+        };
+
+        let longest_name_match = self
+            .constants
+            .iter()
+            .map(|(name, _)| common_prefix_len(input, name))
+            .max()
+            .unwrap_or(0);
+
+        ArgParseRes::Failed {
+            parsed_up_to: number_parsed_up_to.max(longest_name_match),
+            reason: self.hint(),
+        }
+    }
+
+    fn suggestion(&self, prefix: &str) -> Vec<String> {
+        self.suggested(prefix)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Values of the suggested constants.
+    fn suggestion_details(
+        &self,
+        prefix: &str,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.suggested(prefix)
+            .map(|(name, value)| {
+                let details = SuggestionDetails {
+                    description: Some(value.to_string()),
+                    ..SuggestionDetails::default()
+                };
+                (name.clone(), details)
+            })
+            .collect()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        let mut hint = self.number.hint();
+        hint.extend(
+            self.constants
+                .iter()
+                .filter(|(_, value)| self.in_range(*value))
+                .map(|(name, _)| name.clone()),
+        );
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        symbolic_int, symbolic_int_for_range, symbolic_int_for_range_and_name,
+        symbolic_int_with_name,
+    };
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+    use crate::input::arg_parser::{ContextFreeArgParser, SuggestionDetails};
+
+    fn value(value: &str) -> SuggestionDetails {
+        SuggestionDetails {
+            description: Some(value.to_string()),
+            ..SuggestionDetails::default()
+        }
+    }
+
+    #[test]
+    fn u16_addresses() {
+        let parser = symbolic_int_for_range_and_name(
+            0u16,
+            0x7fff,
+            vec![
+                ("UART0_BASE", 0x1000),
+                ("UART1_BASE", 0x1100),
+                ("ROM_BASE", 0x8000),
+            ],
+            "addr",
+        );
+        let expected_hint = &["<addr: 0-32767>", "UART0_BASE", "UART1_BASE"];
+
+        assert_eq!(
+            parser.suggestion_details("UART"),
+            vec![
+                ("UART0_BASE".to_string(), value("4096")),
+                ("UART1_BASE".to_string(), value("4352")),
+            ]
+        );
+        assert_eq!(parser.suggestion_details("R"), vec![]);
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("UART0_BASE", 0x1000);
+        check_parse("UART1_BASE", 0x1100);
+        check_parse("0", 0);
+        check_parse("4096", 4096);
+
+        check_failure("ROM_BASE", 8, &["max addr: 32767"]);
+        check_failure("32768", 5, &["max addr: 32767"]);
+        check_failure("", 0, expected_hint);
+        check_failure("UART", 4, expected_hint);
+        check_failure("UART2_BASE", 4, expected_hint);
+        check_failure("12x", 2, expected_hint);
+        check_failure("x", 0, expected_hint);
+
+        check_suggestions("", &["UART0_BASE", "UART1_BASE"]);
+        check_suggestions("U", &["UART0_BASE", "UART1_BASE"]);
+        check_suggestions("UART1", &["UART1_BASE"]);
+        check_suggestions("UART1_BASE", &[]);
+        check_suggestions("R", &[]);
+        check_suggestions("1", &[]);
+    }
+
+    #[test]
+    fn signed() {
+        let parser = symbolic_int(vec![("MIN", i8::MIN), ("MAX", i8::MAX)]);
+        let expected_hint = &["<-128 - 127>", "MIN", "MAX"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("MIN", -128);
+        check_parse("MAX", 127);
+        check_parse("-5", -5);
+
+        check_failure("-", 0, expected_hint);
+        check_failure("MA", 2, expected_hint);
+
+        check_suggestions("M", &["MIN", "MAX"]);
+    }
+
+    #[test]
+    fn range_and_name() {
+        let (check_hint, _, check_parse, check_failure) =
+            build_cf_parse_checkers(
+                "parser",
+                symbolic_int_for_range(1u32, 10, vec![("TEN", 10)]),
+            );
+
+        check_hint(&["<1-10>", "TEN"]);
+        check_parse("TEN", 10);
+        check_failure("0", 1, &["min: 1"]);
+
+        let (check_hint, _, check_parse, _) = build_cf_parse_checkers(
+            "parser",
+            symbolic_int_with_name(vec![("ALL", u8::MAX)], "mask"),
+        );
+
+        check_hint(&["<mask: 0-255>", "ALL"]);
+        check_parse("ALL", 255);
+    }
+}
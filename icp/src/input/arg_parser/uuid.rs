@@ -0,0 +1,153 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryInto;
+use std::fmt::{self, Display};
+
+use super::hex_bytes::parse_hex_template;
+use super::{ArgParseRes, ContextFreeArgParser};
+
+const HYPHENATED: &str = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+const SIMPLE: &str = "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx";
+
+/// A 128 bit UUID.  Formatted in the hyphenated form, using lower case
+/// digits.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Uuid(pub [u8; 16]);
+
+impl Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if [4, 6, 8, 10].contains(&i) {
+                write!(f, "-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses UUIDs in the hyphenated form, like
+/// `67e55044-10b1-426f-9247-bb680e5fe0c8`, or in the simple form, like
+/// `67e5504410b1426f9247bb680e5fe0c8`.  Both upper and lower case digits are
+/// accepted.
+#[derive(PartialEq, Clone, Debug)]
+pub struct UuidArgParser {
+    name: Option<String>,
+}
+
+/// Accepts a UUID in either of the supported forms.
+pub fn uuid() -> UuidArgParser {
+    UuidArgParser { name: None }
+}
+
+/// Names the argument.  Name will be included in the hint.
+pub fn uuid_with_name<Name>(name: Name) -> UuidArgParser
+where
+    Name: Into<String>,
+{
+    UuidArgParser {
+        name: Some(name.into()),
+    }
+}
+
+impl ContextFreeArgParser<Uuid> for UuidArgParser {
+    fn parse(&self, input: &str) -> ArgParseRes<Uuid> {
+        // Hyphenated form has a hyphen after the first 8 digits.
+        let template = match input.as_bytes().get(8) {
+            Some(b'-') => HYPHENATED,
+            _ => SIMPLE,
+        };
+
+        match parse_hex_template(input, template) {
+            Ok(bytes) => ArgParseRes::Parsed(Uuid(bytes.try_into().unwrap())),
+            Err(parsed_up_to) => ArgParseRes::Failed {
+                parsed_up_to,
+                reason: self.hint(),
+            },
+        }
+    }
+
+    fn suggestion(&self, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn hint(&self) -> Vec<String> {
+        match &self.name {
+            Some(name) => vec![format!("<{}>", name)],
+            None => vec!["<uuid>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{uuid, uuid_with_name, Uuid};
+
+    use crate::input::arg_parser::test_utils::build_cf_parse_checkers;
+
+    const BYTES: [u8; 16] = [
+        0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68,
+        0x0e, 0x5f, 0xe0, 0xc8,
+    ];
+
+    #[test]
+    fn forms() {
+        let parser = uuid();
+        let expected_hint = &["<uuid>"];
+
+        let (check_hint, check_suggestions, check_parse, check_failure) =
+            build_cf_parse_checkers("parser", parser);
+
+        check_hint(expected_hint);
+
+        check_parse("67e55044-10b1-426f-9247-bb680e5fe0c8", Uuid(BYTES));
+        check_parse("67E55044-10B1-426F-9247-BB680E5FE0C8", Uuid(BYTES));
+        check_parse("67e5504410b1426f9247bb680e5fe0c8", Uuid(BYTES));
+
+        check_failure("", 0, expected_hint);
+        check_failure("67e55044-10b1-426f-9247", 23, expected_hint);
+        check_failure("67e55044-10b1-426f-9247-bb680e5fe0c", 35, expected_hint);
+        check_failure(
+            "67e55044-10b1-426f-9247-bb680e5fe0c8a",
+            36,
+            expected_hint,
+        );
+        check_failure(
+            "67e55044-10b1-426g-9247-bb680e5fe0c8",
+            17,
+            expected_hint,
+        );
+        check_failure("67e55044-10b1426f-9247-bb680e5fe0c8", 13, expected_hint);
+        check_failure("67e55044_10b1_426f_9247_bb680e5fe0c8", 8, expected_hint);
+        check_failure("67e5504410b1426f9247bb680e5fe0c", 31, expected_hint);
+        check_failure("67e5504410b1-426f9247bb680e5fe0c8", 12, expected_hint);
+
+        check_suggestions("", &[]);
+        check_suggestions("67e5", &[]);
+    }
+
+    #[test]
+    fn named_and_display() {
+        let (check_hint, _, _, _) =
+            build_cf_parse_checkers("parser", uuid_with_name("device id"));
+
+        check_hint(&["<device id>"]);
+
+        assert_eq!(
+            Uuid(BYTES).to_string(),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+    }
+}
@@ -45,7 +45,9 @@ use regex::Regex;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
 
-use super::arg_parser::{Arg2Parser, ArgParseRes, ContextFreeArgParser};
+use super::arg_parser::{
+    Arg2Parser, ArgParseRes, ContextFreeArgParser, SuggestionDetails,
+};
 
 pub use alternatives::alternatives_cmd;
 
@@ -54,11 +56,16 @@ pub use alternatives::alternatives_cmd;
 pub enum CommandParseFailure {
     /// Failed to parse one of the arguments.
     ArgumentParseFailed {
-        /// First character of the argument that failed to parse.
+        /// First character of the argument that failed to parse, or of the
+        /// part of the argument the argument parser has pointed at.  See
+        /// [`ContextFreeArgParser::failure_span()`].
+        ///
+        /// [`ContextFreeArgParser::failure_span()`]:
+        ///     super::arg_parser::ContextFreeArgParser::failure_span
         from: usize,
 
-        /// Position just after the last character forming the argument that
-        /// failed to parse.
+        /// Position just after the last character forming the argument, or
+        /// the part of it, that failed to parse.
         to: usize,
 
         /// Explanation as to why this input did not match the expectation.  In
@@ -236,6 +243,20 @@ pub trait CommandParser<Res> {
         pos: Option<usize>,
     ) -> (CommandParseRes<Res>, Option<CommandSuggestions>);
 
+    /// Details for some of the suggestions [`parse()`] produces for the same
+    /// `input` and `pos`, as produced by
+    /// [`ContextFreeArgParser::suggestion_details()`].  Suggestions without
+    /// details are omitted.  Default implementation returns no details.
+    ///
+    /// [`parse()`]: Self::parse
+    fn suggestion_details(
+        &self,
+        _input: &str,
+        _pos: usize,
+    ) -> Vec<(String, SuggestionDetails)> {
+        vec![]
+    }
+
     /// It is very common to box command parsers, as their type parameters are
     /// not always expressible, and thus, one can not write an actual type of a
     /// command parser.  It also happens when we want to put parsers of
@@ -610,7 +631,6 @@ macro_rules! define_command_parser {
         }
 
         impl<$( $parser_tyvar, $res_tyvar, )* Combine, $final_res_tyvar>
-            CommandParser<$final_res_tyvar> for
             $type_name<
                 $( $parser_tyvar, $res_tyvar, )*
                 Combine,
@@ -629,8 +649,12 @@ macro_rules! define_command_parser {
             // Unfortunately, ignoring warnings at the expression level is
             // currently a nightly only feature, so ignore for the whole method.
             #[allow(unused_assignments)]
-            fn parse(&self, input: &str, pos: Option<usize>)
-                -> (CommandParseRes<Res>, Option<CommandSuggestions>)
+            fn parse_with_details(&self, input: &str, pos: Option<usize>)
+                -> (
+                    CommandParseRes<$final_res_tyvar>,
+                    Option<CommandSuggestions>,
+                    Vec<(String, SuggestionDetails)>,
+                )
             {
                 lazy_static! {
                     static ref WORD: Regex = Regex::new(r"\S+").unwrap();
@@ -642,6 +666,7 @@ macro_rules! define_command_parser {
 
                 let mut args_iter = WORD.find_iter(input);
                 let mut suggestions = None;
+                let mut details = vec![];
 
                 // Remember the previous argument end character in order to know
                 // if we need to provide suggestions for an absent argument at
@@ -653,17 +678,42 @@ macro_rules! define_command_parser {
                     let $res_var = match args_iter.next() {
                         Some(m) => {
                             let start = m.start();
-                            let end = m.end();
-                            let arg = m.as_str();
+                            let consumes_rest = self.$parser_field
+                                .consumes_rest($( &$prev_parser_res, )*);
+
+                            // Parsers that consume the rest of the input get
+                            // all the remaining words at once, and provide
+                            // suggestions anywhere after the argument start.
+                            let end = if consumes_rest {
+                                args_iter
+                                    .by_ref()
+                                    .last()
+                                    .map_or(m.end(), |last| last.end())
+                            } else {
+                                m.end()
+                            };
+                            let arg = &input[start..end];
 
                             if let Some(pos) = pos {
-                                if start <= pos && pos <= end {
+                                // Whitespace after the last word still belongs
+                                // to an argument that consumes the rest, as
+                                // this is where its next value is typed.
+                                let arg_end = if consumes_rest {
+                                    input.len()
+                                } else {
+                                    end
+                                };
+                                if start <= pos && pos <= arg_end {
                                     let prefix = &input[start..pos];
                                     suggestions = Some(CommandSuggestions(
                                             self.$parser_field.suggestion(
                                                 $( &$prev_parser_res, )* prefix
                                             )
                                     ));
+                                    details = self.$parser_field
+                                        .suggestion_details(
+                                            $( &$prev_parser_res, )* prefix
+                                        );
                                 }
                             }
 
@@ -671,8 +721,23 @@ macro_rules! define_command_parser {
                                 $( &$prev_parser_res, )* arg);
                             let res = match parse {
                                 ArgParseRes::Parsed(res) => res,
-                                ArgParseRes::Failed { parsed_up_to, reason }
-                                    => return (
+                                ArgParseRes::Failed {
+                                    parsed_up_to,
+                                    reason,
+                                } => {
+                                    // Parsers may point at the part of the
+                                    // argument that is at fault.
+                                    let span = self.$parser_field
+                                        .failure_span(
+                                            $( &$prev_parser_res, )* arg
+                                        );
+                                    let (from, to) = match span {
+                                        Some(span) =>
+                                            (start + span.start,
+                                             start + span.end),
+                                        None => (start, end),
+                                    };
+                                    return (
                                         CommandParseRes::Failed {
                                             parsed_up_to: if parsed_up_to > 0 {
                                                 start + parsed_up_to
@@ -680,13 +745,15 @@ macro_rules! define_command_parser {
                                                 prev_arg_end
                                             },
                                             reason: ArgumentParseFailed {
-                                                from: start,
-                                                to: end,
+                                                from,
+                                                to,
                                                 reason,
                                             },
                                         },
                                         suggestions,
-                                    ),
+                                        details,
+                                    );
+                                }
                             };
 
                             prev_arg_end = end;
@@ -699,28 +766,44 @@ macro_rules! define_command_parser {
                             // suggestions for it.  Otherwise we need at least
                             // one extra character after the previous argument
                             // end.
-                            if let Some(pos) = pos {
+                            //
+                            // If an optional argument was absent, suggestions
+                            // have already been provided for it, as it is the
+                            // one that would be typed next.
+                            if let (Some(pos), None) = (pos, &suggestions) {
                                 if prev_arg_end == 0 || pos > prev_arg_end {
                                     suggestions = Some(CommandSuggestions(
                                             self.$parser_field.suggestion(
                                                 $( &$prev_parser_res, )* ""
                                             )
                                     ));
+                                    details = self.$parser_field
+                                        .suggestion_details(
+                                            $( &$prev_parser_res, )* ""
+                                        );
                                 }
                             }
 
-                            let hint = self.$parser_field.hint(
+                            let absent = self.$parser_field.absent_value(
                                 $( &$prev_parser_res, )*);
-                            return (
-                                CommandParseRes::Failed {
-                                    parsed_up_to: prev_arg_end,
-                                    reason: ExpectedArg {
-                                        index: $arg_index,
-                                        hint,
-                                    },
-                                },
-                                suggestions,
-                            );
+                            match absent {
+                                Some(res) => res,
+                                None => {
+                                    let hint = self.$parser_field.hint(
+                                        $( &$prev_parser_res, )*);
+                                    return (
+                                        CommandParseRes::Failed {
+                                            parsed_up_to: prev_arg_end,
+                                            reason: ExpectedArg {
+                                                index: $arg_index,
+                                                hint,
+                                            },
+                                        },
+                                        suggestions,
+                                        details,
+                                    );
+                                }
+                            }
                         }
                     };
                 )*
@@ -739,8 +822,38 @@ macro_rules! define_command_parser {
                     }
                 };
 
+                (res, suggestions, details)
+            }
+        }
+
+        impl<$( $parser_tyvar, $res_tyvar, )* Combine, $final_res_tyvar>
+            CommandParser<$final_res_tyvar> for
+            $type_name<
+                $( $parser_tyvar, $res_tyvar, )*
+                Combine,
+                $final_res_tyvar,
+            >
+        where
+            $(
+                $parser_tyvar:
+                    $arg_parser_trait< $( $arg_parser_trait_arg, )* >,
+            )*
+            Combine: Fn( $( $res_tyvar, )* ) -> $final_res_tyvar,
+        {
+            fn parse(&self, input: &str, pos: Option<usize>)
+                -> (CommandParseRes<Res>, Option<CommandSuggestions>)
+            {
+                let (res, suggestions, _) =
+                    self.parse_with_details(input, pos);
                 (res, suggestions)
             }
+
+            fn suggestion_details(&self, input: &str, pos: usize)
+                -> Vec<(String, SuggestionDetails)>
+            {
+                let (_, _, details) = self.parse_with_details(input, Some(pos));
+                details
+            }
         }
     };
 }
@@ -766,10 +879,14 @@ define_command_parser!(
 mod tests {
     use super::{
         command_1arg, command_2args, command_no_args, ArgParseRes,
-        CommandParseFailure, CommandSuggestions,
+        CommandParseFailure, CommandParser, CommandSuggestions,
     };
 
-    use crate::input::arg_parser::keyword_set_with_hint;
+    use crate::input::arg_parser::{
+        at_least, expr, keyword_set, keyword_set_with_descriptions,
+        keyword_set_with_hint, with_default, ContextFreeArgParser, Radix,
+        SuggestionDetails,
+    };
     use crate::input::command_parser::test_utils::check_parse_and_failure_generator;
 
     use mocks::MockArg2Parser;
@@ -1126,6 +1243,199 @@ mod tests {
         }
     }
 
+    #[test]
+    fn optional_trailing_arg() {
+        let arg1 = keyword_set(["duplex"]);
+        let arg2 = with_default(keyword_set(["full", "half"]), "full".into());
+        let parser =
+            command_2args(arg1, arg2.adapt(), |keyword, mode| (keyword, mode));
+
+        let (check_parse, check_failure) =
+            check_parse_and_failure_generator(parser);
+
+        use CommandParseFailure::ArgumentParseFailed;
+
+        let parsed = |mode: &str| ("duplex".to_string(), mode.to_string());
+
+        check_parse("duplex", None, parsed("full"), None);
+        check_parse(
+            "duplex",
+            Some(6),
+            parsed("full"),
+            Some(CommandSuggestions(vec_str![])),
+        );
+        check_parse(
+            "duplex ",
+            Some(7),
+            parsed("full"),
+            Some(CommandSuggestions(vec_str!["full", "half"])),
+        );
+        check_parse("duplex half", None, parsed("half"), None);
+
+        check_failure(
+            "duplex auto",
+            None,
+            6,
+            ArgumentParseFailed {
+                from: 7,
+                to: 11,
+                reason: vec_str!["full", "half"],
+            },
+            None,
+        );
+    }
+
+    #[test]
+    fn repeated_trailing_arg() {
+        let arg1 = keyword_set(["load"]);
+        let arg2 = at_least(keyword_set(["boot", "main"]), 1);
+        let parser = command_2args(arg1, arg2.adapt(), |_, files| files);
+
+        let (check_parse, check_failure) =
+            check_parse_and_failure_generator(parser);
+
+        use CommandParseFailure::{ArgumentParseFailed, ExpectedArg};
+
+        check_parse("load boot", None, vec_str!["boot"], None);
+        check_parse("load main  boot", None, vec_str!["main", "boot"], None);
+
+        let input = "load main b";
+        //           0         1
+        //           012345678901
+
+        check_failure(
+            input,
+            Some(11),
+            11,
+            ArgumentParseFailed {
+                from: 5,
+                to: 11,
+                reason: vec_str!["boot", "main"],
+            },
+            Some(CommandSuggestions(vec_str!["main boot"])),
+        );
+        check_parse(
+            "load main ",
+            Some(10),
+            vec_str!["main"],
+            Some(CommandSuggestions(vec_str!["main boot", "main main"])),
+        );
+        // Cursor past the end of the input is outside of any argument.
+        check_parse("load main", Some(10), vec_str!["main"], None);
+
+        check_failure(
+            "load",
+            None,
+            4,
+            ExpectedArg {
+                index: 1,
+                hint: vec_str!["boot...", "main..."],
+            },
+            None,
+        );
+    }
+
+    #[test]
+    fn suggestion_details() {
+        let arg1 = keyword_set(["mode"]);
+        let arg2 = keyword_set_with_descriptions([
+            ("fast", "Skip verification"),
+            ("full", "Verify every block"),
+            ("quick", "Same as fast"),
+        ]);
+        let parser = command_2args(arg1, arg2.adapt(), |_, mode| mode);
+
+        let details = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(keyword, description)| {
+                    (
+                        keyword.to_string(),
+                        SuggestionDetails {
+                            description: Some(description.to_string()),
+                            ..SuggestionDetails::default()
+                        },
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            parser.suggestion_details("mode f", 6),
+            details(&[
+                ("fast", "Skip verification"),
+                ("full", "Verify every block"),
+            ]),
+        );
+        assert_eq!(parser.suggestion_details("mode quick", 0), vec![]);
+
+        let (check_parse, check_failure) =
+            check_parse_and_failure_generator(parser);
+
+        check_failure(
+            "mode f",
+            Some(6),
+            6,
+            CommandParseFailure::ArgumentParseFailed {
+                from: 5,
+                to: 6,
+                reason: vec_str!["fast", "full", "quick"],
+            },
+            Some(CommandSuggestions(vec_str!["fast", "full"])),
+        );
+        check_parse(
+            "mode quick",
+            Some(0),
+            "quick".to_string(),
+            Some(CommandSuggestions(vec_str!["mode"])),
+        );
+    }
+
+    #[test]
+    fn failure_span() {
+        let arg1 = keyword_set(["read"]);
+        let arg2 = expr::<u32>(Radix::Hex)
+            .with_symbols(vec![("base".to_string(), 0x4000)]);
+        let parser = command_2args(arg1, arg2.adapt(), |_, addr| addr);
+
+        let (check_parse, check_failure) =
+            check_parse_and_failure_generator(parser);
+
+        use CommandParseFailure::ArgumentParseFailed;
+
+        check_parse("read base+0x40*4", None, 0x4100, None);
+
+        let input = "read base+0x4g*4";
+        //           0         1
+        //           0123456789012345
+
+        // Only the invalid token is highlighted.
+        check_failure(
+            input,
+            None,
+            13,
+            ArgumentParseFailed {
+                from: 13,
+                to: 14,
+                reason: vec_str!["expected an operator"],
+            },
+            None,
+        );
+
+        // Range checks fail for the whole argument.
+        check_failure(
+            "read 0x1_0000_0000",
+            None,
+            18,
+            ArgumentParseFailed {
+                from: 5,
+                to: 18,
+                reason: vec_str!["max: 0xffffffff"],
+            },
+            None,
+        );
+    }
+
     mod mocks {
         use std::cell::RefCell;
         use std::fmt::Debug;
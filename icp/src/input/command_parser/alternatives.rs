@@ -14,6 +14,8 @@
 
 use super::{CommandParseRes, CommandParser, CommandSuggestions};
 
+use crate::input::arg_parser::SuggestionDetails;
+
 /// This parser combines several parsers, that all return the same result type,
 /// trying them one by one, in order.  It is designed to be used with commands
 /// that have several different forms.  The first parser, in order, that
@@ -73,6 +75,17 @@ impl<Res> CommandParser<Res> for AlternativesCommandParser<Res> {
 
         (combined_res, combined_suggestions)
     }
+
+    fn suggestion_details(
+        &self,
+        input: &str,
+        pos: usize,
+    ) -> Vec<(String, SuggestionDetails)> {
+        self.parsers
+            .iter()
+            .flat_map(|parser| parser.suggestion_details(input, pos))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -14,8 +14,6 @@
 
 //! A helper to find a common prefix of a set of strings.
 
-use std::cmp::min;
-
 pub fn common_prefix<'a>(
     mut options: impl Iterator<Item = &'a str>,
 ) -> &'a str {
@@ -25,25 +23,34 @@ pub fn common_prefix<'a>(
     };
 
     for next in options {
-        let mut matched_end = min(res.len(), next.len());
-        for ((res_char_i, res_char), next_char) in
-            res.char_indices().zip(next.chars())
-        {
-            if res_char != next_char {
-                matched_end = res_char_i;
-                break;
-            }
-        }
-
-        res = &res[0..matched_end];
+        res = &res[0..common_prefix_len(res, next)];
     }
 
     res
 }
 
+/// Length, in bytes, of the common prefix of `s1` and `s2`.
+pub fn common_prefix_len(s1: &str, s2: &str) -> usize {
+    common_prefix_len_by(s1, s2, |c1, c2| c1 == c2)
+}
+
+/// Length, in bytes of `s1`, of the common prefix of `s1` and `s2`, when
+/// characters are compared with `eq`.  Characters that are equal according to
+/// `eq` may have different lengths, like `k` and the Kelvin sign, when case
+/// is ignored.
+pub fn common_prefix_len_by<Eq>(s1: &str, s2: &str, eq: Eq) -> usize
+where
+    Eq: Fn(char, char) -> bool,
+{
+    let mut s2 = s2.chars();
+    s1.char_indices()
+        .find(|&(_, c1)| !s2.next().is_some_and(|c2| eq(c1, c2)))
+        .map_or(s1.len(), |(i, _)| i)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::common_prefix;
+    use super::{common_prefix, common_prefix_len, common_prefix_len_by};
 
     #[test]
     fn common_prefix_basic() {
@@ -54,4 +61,25 @@ mod tests {
         assert_eq!(common_prefix(vec!["abc", "axy", "def"].into_iter()), "");
         assert_eq!(common_prefix(vec!["abc", "aby", "abef"].into_iter()), "ab");
     }
+
+    #[test]
+    fn common_prefix_len_basic() {
+        assert_eq!(common_prefix_len("", "abc"), 0);
+        assert_eq!(common_prefix_len("abc", "abc"), 3);
+        assert_eq!(common_prefix_len("abc", "ab"), 2);
+        assert_eq!(common_prefix_len("abc", "axc"), 1);
+        assert_eq!(common_prefix_len("µs", "µm"), 2);
+    }
+
+    #[test]
+    fn common_prefix_len_by_ignoring_case() {
+        let ignore_case =
+            |c1: char, c2: char| c1.to_lowercase().eq(c2.to_lowercase());
+
+        assert_eq!(common_prefix_len_by("ABC", "abd", ignore_case), 2);
+        assert_eq!(common_prefix_len_by("ab", "ABC", ignore_case), 2);
+        // The Kelvin sign is 3 bytes long, while its lower case is `k`.
+        assert_eq!(common_prefix_len_by("\u{212A}m", "kb", ignore_case), 3);
+        assert_eq!(common_prefix_len_by("k", "\u{212A}", ignore_case), 1);
+    }
 }
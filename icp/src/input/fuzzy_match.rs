@@ -0,0 +1,176 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fuzzy matching of a pattern against a candidate string, where characters of
+//! the pattern need to appear in the candidate in the same order, but not
+//! necessarily next to each other.  Like `ctrl` matching `UART_CTRL_REG`.
+
+/// Every matched character.
+const MATCH_SCORE: i64 = 16;
+/// Matched character starts a word.
+const BOUNDARY_BONUS: i64 = 8;
+/// Matched character immediately follows the previous matched character.
+const CONSECUTIVE_BONUS: i64 = 8;
+/// Every character that is skipped before or between the matched ones.
+const GAP_PENALTY: i64 = 1;
+
+/// Successful match of a pattern against a candidate.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FuzzyMatch {
+    /// Higher scores mean better matches.  Only comparable between matches of
+    /// the same pattern.
+    pub score: i64,
+    /// Character positions in the candidate, that matched the pattern
+    /// characters.  Frontends may use these to highlight the matched parts.
+    pub positions: Vec<usize>,
+}
+
+/// Compares characters, optionally ignoring case.  Case is compared using the
+/// Unicode lower case mapping.
+pub(crate) fn chars_eq(c1: char, c2: char, ignore_case: bool) -> bool {
+    c1 == c2 || (ignore_case && c1.to_lowercase().eq(c2.to_lowercase()))
+}
+
+/// Word starts are the first character, characters that follow a
+/// non-alphanumeric character, like `_` or `-`, and upper case characters
+/// that follow lower case ones.
+fn is_word_start(candidate: &[char], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+
+    let prev = candidate[i - 1];
+    let cur = candidate[i];
+    (!prev.is_alphanumeric() && cur.is_alphanumeric())
+        || (prev.is_lowercase() && cur.is_uppercase())
+}
+
+/// Matches `pattern` against `candidate`, finding the placement of the
+/// pattern characters with the highest score.  Returns `None` if `candidate`
+/// does not contain all the `pattern` characters, in order.
+pub fn fuzzy_match(
+    pattern: &str,
+    candidate: &str,
+    ignore_case: bool,
+) -> Option<FuzzyMatch> {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let candidate = candidate.chars().collect::<Vec<_>>();
+
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: vec![],
+        });
+    }
+
+    // `best[i][j]` is the best score for matching `pattern[..=i]`, with
+    // `pattern[i]` matched to `candidate[j]`.  `from[i][j]` is the position
+    // that `pattern[i - 1]` was matched to, in that case.
+    let mut best = vec![vec![None; candidate.len()]; pattern.len()];
+    let mut from = vec![vec![0; candidate.len()]; pattern.len()];
+
+    for (i, p) in pattern.iter().enumerate() {
+        for j in i..candidate.len() {
+            if !chars_eq(*p, candidate[j], ignore_case) {
+                continue;
+            }
+
+            let mut score = MATCH_SCORE;
+            if is_word_start(&candidate, j) {
+                score += BOUNDARY_BONUS;
+            }
+
+            if i == 0 {
+                best[i][j] = Some(score - GAP_PENALTY * j as i64);
+                continue;
+            }
+
+            let prev = (i - 1..j)
+                .filter_map(|k| {
+                    best[i - 1][k].map(|prev_score| {
+                        let link = if k + 1 == j {
+                            CONSECUTIVE_BONUS
+                        } else {
+                            -GAP_PENALTY * (j - k - 1) as i64
+                        };
+                        (prev_score + link, k)
+                    })
+                })
+                // Prefer later positions on ties, to keep matches together.
+                .max_by_key(|(score, k)| (*score, *k));
+
+            if let Some((prev_score, k)) = prev {
+                best[i][j] = Some(prev_score + score);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (score, end) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (score, j)))
+        .max_by_key(|(score, j)| (*score, std::cmp::Reverse(*j)))?;
+
+    let mut positions = vec![end];
+    for i in (1..pattern.len()).rev() {
+        let prev = from[i][positions[positions.len() - 1]];
+        positions.push(prev);
+    }
+    positions.reverse();
+
+    Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_match;
+
+    fn positions(pattern: &str, candidate: &str) -> Option<Vec<usize>> {
+        fuzzy_match(pattern, candidate, true).map(|m| m.positions)
+    }
+
+    fn score(pattern: &str, candidate: &str) -> i64 {
+        fuzzy_match(pattern, candidate, true).unwrap().score
+    }
+
+    #[test]
+    fn matches() {
+        assert_eq!(positions("", "abc"), Some(vec![]));
+        assert_eq!(positions("abc", "abc"), Some(vec![0, 1, 2]));
+        assert_eq!(positions("ac", "abc"), Some(vec![0, 2]));
+        assert_eq!(positions("ctrl", "UART_CTRL_REG"), Some(vec![5, 6, 7, 8]));
+        assert_eq!(positions("ucr", "UART_CTRL_REG"), Some(vec![0, 5, 10]));
+        assert_eq!(positions("sb", "setBaud"), Some(vec![0, 3]));
+        assert_eq!(positions("µs", "xµys"), Some(vec![1, 3]));
+
+        assert_eq!(positions("cb", "abc"), None);
+        assert_eq!(positions("abcd", "abc"), None);
+
+        assert_eq!(fuzzy_match("ABC", "abc", false), None);
+    }
+
+    #[test]
+    fn ranking() {
+        // Contiguous matches beat scattered ones.
+        assert!(
+            score("ctrl", "UART_CTRL_REG") > score("ctrl", "CAN_TX_RX_LEN")
+        );
+        // Word starts beat matches in the middle of a word.
+        assert!(score("reg", "UART_REG") > score("reg", "UART_PREGAP"));
+        // Earlier matches beat later ones.
+        assert!(score("uart", "UART_CTRL") > score("uart", "CTRL_UART"));
+    }
+}